@@ -30,6 +30,7 @@ async fn main() -> Result<()> {
         .with_max_level(tracing::Level::DEBUG)
         .finish();
     tracing::subscriber::set_global_default(subscriber)?;
+    mongoose::init(mongoose::Config::from_env()).await?;
     // insert one
     {
         let start = std::time::Instant::now();
@@ -0,0 +1,151 @@
+use syn::{meta::ParseNestedMeta, Data, DeriveInput, Field, Fields, LitStr};
+
+// named fields of a struct, anything else is rejected
+pub fn named_fields<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<Vec<&'a Field>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields.named.iter().collect()),
+            _ => Err(syn::Error::new_spanned(
+                input,
+                format!("{derive} can only be derived for structs with named fields"),
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            input,
+            format!("{derive} can only be derived for structs"),
+        )),
+    }
+}
+
+// the serialized name from `rename = "..."` or `rename(serialize = "...")`
+fn serialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|inner| {
+        let value: LitStr = inner.value()?.parse()?;
+        if inner.path.is_ident("serialize") {
+            name = Some(value);
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+// skips the value of serde attributes we do not care about
+fn skip_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|inner| skip_value(&inner))?;
+    }
+    Ok(())
+}
+
+// serde's field renaming rules, applied to the snake cased field name
+fn apply_rule(rule: &LitStr, field: &str) -> syn::Result<String> {
+    let pascal = || {
+        let mut pascal = String::new();
+        let mut capitalize = true;
+        for ch in field.chars() {
+            if ch == '_' {
+                capitalize = true;
+            } else if capitalize {
+                pascal.push(ch.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                pascal.push(ch);
+            }
+        }
+        pascal
+    };
+    Ok(match rule.value().as_str() {
+        "lowercase" | "snake_case" => field.to_string(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field.to_ascii_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_lowercase().to_string() + chars.as_str()
+            })
+        }
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.to_ascii_uppercase().replace('_', "-"),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                rule,
+                "unknown serde rename_all rule",
+            ))
+        }
+    })
+}
+
+// the container's `#[serde(rename_all = "...")]`, or its `serialize` rule
+fn rename_all(input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let mut rule = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                rule = serialize_name(&meta)?.or_else(|| rule.take());
+            } else {
+                skip_value(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+    Ok(rule)
+}
+
+// the key a field is stored under, honoring `#[serde(rename = "...")]` on the field and
+// `#[serde(rename_all = "...")]` on the struct
+pub fn serde_name(input: &DeriveInput, field: &Field) -> syn::Result<String> {
+    let ident = field
+        .ident
+        .as_ref()
+        .map(|ident| ident.to_string().trim_start_matches("r#").to_string())
+        .unwrap_or_default();
+    let mut name = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                name = serialize_name(&meta)?
+                    .map(|rename| rename.value())
+                    .or_else(|| name.take());
+            } else {
+                skip_value(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+    match (name, rename_all(input)?) {
+        (Some(name), _) => Ok(name),
+        (None, Some(rule)) => apply_rule(&rule, &ident),
+        (None, None) => Ok(ident),
+    }
+}
+
+// whether a field carries any of the given bare or `key = value` serde arguments
+pub fn serde_flag(field: &Field, flags: &[&str]) -> syn::Result<bool> {
+    let mut found = false;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+    {
+        attr.parse_nested_meta(|meta| {
+            found |= flags.iter().any(|flag| meta.path.is_ident(flag));
+            skip_value(&meta)
+        })?;
+    }
+    Ok(found)
+}
@@ -0,0 +1,140 @@
+use crate::attr;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{DeriveInput, Field, GenericArgument, PathArguments, Type};
+
+#[derive(Default)]
+struct FieldAttrs {
+    // `#[fields(nested)]`, the field's type derives `Fields` too
+    nested: bool,
+    // `#[fields(skip)]`, no path is generated
+    skip: bool,
+}
+
+impl FieldAttrs {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("fields"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("nested") {
+                    attrs.nested = true;
+                } else if meta.path.is_ident("skip") {
+                    attrs.skip = true;
+                } else {
+                    return Err(meta.error("unsupported fields attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attrs)
+    }
+}
+
+// paths into `Option<T>`, `Vec<T>` and `Box<T>` are the paths into `T`
+fn inner_type(ty: &Type) -> &Type {
+    let Type::Path(path) = ty else {
+        return ty;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return ty;
+    };
+    if !["Option", "Vec", "Box"].contains(&segment.ident.to_string().as_str()) {
+        return ty;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => match arguments.args.first() {
+            Some(GenericArgument::Type(inner)) => inner_type(inner),
+            _ => ty,
+        },
+        _ => ty,
+    }
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = attr::named_fields(input, "Fields")?;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Fields can not be derived for generic structs",
+        ));
+    }
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let paths = format_ident!("{}Fields", ident);
+    let mut methods = vec![];
+    for field in fields {
+        let attrs = FieldAttrs::parse(field)?;
+        if attrs.skip || attr::serde_flag(field, &["skip", "skip_serializing"])? {
+            continue;
+        }
+        let name = &field.ident;
+        let ty = inner_type(&field.ty);
+        if attr::serde_flag(field, &["flatten"])? {
+            // flattened keys are stored at this level, so they get no segment of their own
+            if !attrs.nested {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "#[serde(flatten)] fields need #[fields(nested)] or #[fields(skip)]",
+                ));
+            }
+            methods.push(quote! {
+                pub fn #name(&self) -> <#ty as ::mongoose::fields::Fields>::Paths {
+                    <#ty as ::mongoose::fields::Fields>::paths(&self.prefix)
+                }
+            });
+            continue;
+        }
+        let key = attr::serde_name(input, field)?;
+        methods.push(if attrs.nested {
+            quote! {
+                pub fn #name(&self) -> <#ty as ::mongoose::fields::Fields>::Paths {
+                    let path = ::mongoose::fields::FieldPath::under(&self.prefix, #key);
+                    <#ty as ::mongoose::fields::Fields>::paths(path.as_str())
+                }
+            }
+        } else {
+            quote! {
+                pub fn #name(&self) -> ::mongoose::fields::FieldPath {
+                    ::mongoose::fields::FieldPath::under(&self.prefix, #key)
+                }
+            }
+        });
+    }
+    Ok(quote! {
+        #[derive(Debug, Clone)]
+        #vis struct #paths {
+            prefix: String,
+        }
+
+        impl #paths {
+            #(#methods)*
+        }
+
+        // the nested struct itself, e.g. for a projection of `address`
+        impl ::std::convert::AsRef<str> for #paths {
+            fn as_ref(&self) -> &str {
+                &self.prefix
+            }
+        }
+
+        impl ::mongoose::fields::Fields for #ident {
+            type Paths = #paths;
+
+            fn paths(prefix: &str) -> #paths {
+                #paths {
+                    prefix: prefix.to_string(),
+                }
+            }
+        }
+
+        impl #ident {
+            #vis fn fields() -> #paths {
+                <Self as ::mongoose::fields::Fields>::paths("")
+            }
+        }
+    })
+}
@@ -0,0 +1,57 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod attr;
+mod fields;
+mod model;
+mod schema;
+mod validate;
+
+// #[derive(Model)]
+// #[model(collection = "people", timestamps, id = "nanoid")]
+// struct Person {
+//     #[serde(rename = "_id")]
+//     id: String,
+//     #[index(unique)]
+//     #[validate(email)]
+//     email: String,
+//     ...
+// }
+#[proc_macro_derive(Model, attributes(model, index, validate))]
+pub fn derive_model(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    model::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// `$jsonSchema` for `Model::apply_schema_validator`, derive it on nested structs too
+// fields stored through `#[serde(with = "...")]` name their type, `#[bson_schema(bson_type = "date")]`
+#[proc_macro_derive(BsonSchema, attributes(bson_schema))]
+pub fn derive_bson_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    schema::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// typed field paths, `User::fields().address().city()` is `"address.city"`
+// mark fields whose type derives `Fields` too with `#[fields(nested)]`, leave one out with `#[fields(skip)]`
+// a `#[serde(flatten)]` field needs either, a nested one puts its paths at the parent level
+#[proc_macro_derive(Fields, attributes(fields))]
+pub fn derive_fields(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    fields::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// for nested structs validated through `#[validate(nested)]`
+// #[validate(length(min = 3, max = 32), regex = "^[a-z_]+$", one_of("US", "CA"), range(min = 1))]
+#[proc_macro_derive(Validate, attributes(validate))]
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    validate::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,272 @@
+use crate::{attr, validate};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{DeriveInput, Field, LitInt, LitStr};
+
+enum IdKind {
+    Nanoid,
+    Uuid,
+}
+
+#[derive(Default)]
+struct ModelAttrs {
+    collection: Option<LitStr>,
+    naming: Option<TokenStream>,
+    connection: Option<LitStr>,
+    database: Option<LitStr>,
+    tenant_field: Option<LitStr>,
+    tenant_database: bool,
+    timestamps: bool,
+    id: Option<IdKind>,
+}
+
+impl ModelAttrs {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("model"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("collection") {
+                    attrs.collection = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("naming") {
+                    let naming: LitStr = meta.value()?.parse()?;
+                    attrs.naming = match naming.value().as_str() {
+                        "inflected" => Some(quote!(Inflected)),
+                        "legacy" => Some(quote!(Legacy)),
+                        _ => return Err(meta.error("naming must be \"inflected\" or \"legacy\"")),
+                    };
+                } else if meta.path.is_ident("connection") {
+                    attrs.connection = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("database") {
+                    attrs.database = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("tenant_field") {
+                    attrs.tenant_field = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("tenant_database") {
+                    attrs.tenant_database = true;
+                } else if meta.path.is_ident("timestamps") {
+                    attrs.timestamps = true;
+                } else if meta.path.is_ident("id") {
+                    let kind: LitStr = meta.value()?.parse()?;
+                    attrs.id = Some(match kind.value().as_str() {
+                        "nanoid" => IdKind::Nanoid,
+                        "uuid" => IdKind::Uuid,
+                        _ => return Err(meta.error("id must be \"nanoid\" or \"uuid\"")),
+                    });
+                } else {
+                    return Err(meta.error("unsupported model attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        if attrs.tenant_database && attrs.tenant_field.is_some() {
+            return Err(syn::Error::new_spanned(
+                input,
+                "tenant_database and tenant_field are mutually exclusive",
+            ));
+        }
+        Ok(attrs)
+    }
+}
+
+#[derive(Default)]
+struct IndexAttrs {
+    unique: bool,
+    sparse: bool,
+    descending: bool,
+    name: Option<LitStr>,
+    expire_after: Option<LitInt>,
+}
+
+impl IndexAttrs {
+    // `None` when the field carries no `#[index]` attribute
+    fn parse(field: &Field) -> syn::Result<Option<Self>> {
+        let mut index = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("index"))
+        {
+            let attrs = index.get_or_insert_with(Self::default);
+            if matches!(attr.meta, syn::Meta::Path(_)) {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("unique") {
+                    attrs.unique = true;
+                } else if meta.path.is_ident("sparse") {
+                    attrs.sparse = true;
+                } else if meta.path.is_ident("desc") {
+                    attrs.descending = true;
+                } else if meta.path.is_ident("name") {
+                    attrs.name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("expire_after") {
+                    attrs.expire_after = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported index attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(index)
+    }
+
+    fn to_index_model(&self, key: &str) -> TokenStream {
+        let order = if self.descending { -1 } else { 1 };
+        let mut options = vec![];
+        if self.unique {
+            options.push(quote!(.unique(true)));
+        }
+        if self.sparse {
+            options.push(quote!(.sparse(true)));
+        }
+        if let Some(name) = &self.name {
+            options.push(quote!(.name(#name.to_string())));
+        }
+        if let Some(seconds) = &self.expire_after {
+            options.push(quote!(.expire_after(::std::time::Duration::from_secs(#seconds))));
+        }
+        quote! {
+            ::mongoose::IndexModel::builder()
+                .keys(::mongoose::doc! { #key: #order })
+                .options(::mongoose::IndexOptions::builder() #(#options)* .build())
+                .build()
+        }
+    }
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let attrs = ModelAttrs::parse(input)?;
+    let fields = attr::named_fields(input, "Model")?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut items = vec![];
+    if let Some(collection) = &attrs.collection {
+        items.push(quote! {
+            const COLLECTION_NAME: Option<&'static str> = Some(#collection);
+        });
+    }
+    if let Some(naming) = &attrs.naming {
+        items.push(quote! {
+            const NAMING: ::mongoose::inflection::Naming = ::mongoose::inflection::Naming::#naming;
+        });
+    }
+    if let Some(connection) = &attrs.connection {
+        items.push(quote! {
+            fn connection_name() -> &'static str {
+                #connection
+            }
+        });
+    }
+    if let Some(database) = &attrs.database {
+        items.push(quote! {
+            fn database_name() -> Option<&'static str> {
+                Some(#database)
+            }
+        });
+    }
+    if let Some(field) = &attrs.tenant_field {
+        items.push(quote! {
+            fn tenancy() -> ::mongoose::tenant::Tenancy {
+                ::mongoose::tenant::Tenancy::Field(#field)
+            }
+        });
+    } else if attrs.tenant_database {
+        items.push(quote! {
+            fn tenancy() -> ::mongoose::tenant::Tenancy {
+                ::mongoose::tenant::Tenancy::Database
+            }
+        });
+    }
+    let mut indexes = vec![];
+    for field in &fields {
+        if let Some(index) = IndexAttrs::parse(field)? {
+            indexes.push(index.to_index_model(&attr::serde_name(input, field)?));
+        }
+    }
+    if !indexes.is_empty() {
+        items.push(quote! {
+            fn indexes() -> Vec<::mongoose::IndexModel> {
+                vec![#(#indexes),*]
+            }
+        });
+    }
+    let checks = validate::checks(input, &fields)?;
+    if !checks.is_empty() {
+        items.push(validate::validate_fn(&checks));
+    }
+
+    let default = if attrs.timestamps || attrs.id.is_some() {
+        expand_default(input, &attrs, &fields)?
+    } else {
+        TokenStream::new()
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::mongoose::Model for #ident #ty_generics #where_clause {
+            #(#items)*
+        }
+        #default
+    })
+}
+
+// `Default` with a generated id and timestamps, every other field uses its own default
+fn expand_default(
+    input: &DeriveInput,
+    attrs: &ModelAttrs,
+    fields: &[&Field],
+) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut has_id = false;
+    let mut has_timestamps = (false, false);
+    let mut values = vec![];
+    for field in fields {
+        let name = field.ident.to_token_stream();
+        let key = attr::serde_name(input, field)?;
+        let value = match (key.as_str(), &attrs.id) {
+            ("_id", Some(IdKind::Nanoid)) => {
+                has_id = true;
+                quote!(<Self as ::mongoose::Model>::generate_nanoid().into())
+            }
+            ("_id", Some(IdKind::Uuid)) => {
+                has_id = true;
+                quote!(<Self as ::mongoose::Model>::generate_uuid().into())
+            }
+            ("created_at" | "updated_at", _) if attrs.timestamps => {
+                if key == "created_at" {
+                    has_timestamps.0 = true;
+                } else {
+                    has_timestamps.1 = true;
+                }
+                quote!(::mongoose::DateTime::now().into())
+            }
+            _ => quote!(::std::default::Default::default()),
+        };
+        values.push(quote!(#name: #value));
+    }
+    if attrs.id.is_some() && !has_id {
+        return Err(syn::Error::new_spanned(
+            input,
+            "id requires a field stored as `_id`, e.g. #[serde(rename = \"_id\")]",
+        ));
+    }
+    if attrs.timestamps && has_timestamps != (true, true) {
+        return Err(syn::Error::new_spanned(
+            input,
+            "timestamps requires `created_at` and `updated_at` fields",
+        ));
+    }
+    Ok(quote! {
+        impl #impl_generics ::std::default::Default for #ident #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#values),*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,127 @@
+use crate::attr;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Field, LitStr, Type};
+
+// the `bsonType` aliases understood by `$jsonSchema`
+const BSON_TYPES: &[&str] = &[
+    "array",
+    "binData",
+    "bool",
+    "date",
+    "dbPointer",
+    "decimal",
+    "double",
+    "int",
+    "javascript",
+    "long",
+    "maxKey",
+    "minKey",
+    "null",
+    "number",
+    "object",
+    "objectId",
+    "regex",
+    "string",
+    "symbol",
+    "timestamp",
+];
+
+// `#[bson_schema(bson_type = "...")]`, the type a field is stored as when its own
+// `BsonSchema` would not describe it, e.g. behind `#[serde(with = "...")]`
+fn bson_type(field: &Field) -> syn::Result<Option<LitStr>> {
+    let mut bson_type = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("bson_schema"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("bson_type") {
+                return Err(meta.error("unsupported bson_schema attribute"));
+            }
+            let value: LitStr = meta.value()?.parse()?;
+            if !BSON_TYPES.contains(&value.value().as_str()) {
+                return Err(syn::Error::new_spanned(value, "unknown bsonType"));
+            }
+            bson_type = Some(value);
+            Ok(())
+        })?;
+    }
+    Ok(bson_type)
+}
+
+fn is_option(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(|segment| segment.ident == "Option"))
+}
+
+// an object schema with a property per field, fields without `Option` or `#[serde(default)]` are required
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = attr::named_fields(input, "BsonSchema")?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut properties = vec![];
+    for field in fields {
+        if attr::serde_flag(field, &["flatten"])? {
+            return Err(syn::Error::new_spanned(
+                field,
+                "BsonSchema does not support #[serde(flatten)]",
+            ));
+        }
+        if attr::serde_flag(field, &["skip", "skip_serializing"])? {
+            continue;
+        }
+        let key = attr::serde_name(input, field)?;
+        let ty = &field.ty;
+        let bson_type = bson_type(field)?;
+        // the serializer decides the stored type, the field's type says nothing about it
+        if bson_type.is_none() && attr::serde_flag(field, &["with", "serialize_with"])? {
+            return Err(syn::Error::new_spanned(
+                field,
+                "fields with #[serde(with = \"...\")] need #[bson_schema(bson_type = \"...\")]",
+            ));
+        }
+        let (schema, required) = match bson_type {
+            // an `Option` field may hold null, or be left out
+            Some(bson_type) if is_option(ty) => (
+                quote!(::mongoose::doc! { "bsonType": [#bson_type, "null"] }),
+                quote!(false),
+            ),
+            Some(bson_type) => (
+                quote!(::mongoose::doc! { "bsonType": #bson_type }),
+                quote!(true),
+            ),
+            None => (
+                quote!(<#ty as ::mongoose::schema::BsonSchema>::bson_schema()),
+                quote!(<#ty as ::mongoose::schema::BsonSchema>::required()),
+            ),
+        };
+        let required = if attr::serde_flag(field, &["default", "skip_serializing_if"])? {
+            quote!(false)
+        } else {
+            required
+        };
+        properties.push(quote! {
+            properties.insert(#key, #schema);
+            if #required {
+                required.push(#key);
+            }
+        });
+    }
+    Ok(quote! {
+        impl #impl_generics ::mongoose::schema::BsonSchema for #ident #ty_generics #where_clause {
+            fn bson_schema() -> ::mongoose::Document {
+                let mut properties = ::mongoose::Document::new();
+                let mut required: Vec<&str> = Vec::new();
+                #(#properties)*
+                let mut schema = ::mongoose::doc! { "bsonType": "object" };
+                // an empty `required` array is rejected by the server
+                if !required.is_empty() {
+                    schema.insert("required", required);
+                }
+                schema.insert("properties", properties);
+                schema
+            }
+        }
+    })
+}
@@ -0,0 +1,155 @@
+use crate::attr;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{punctuated::Punctuated, DeriveInput, Expr, Field, LitStr, Token, Type};
+
+enum Check {
+    Length {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    Range {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    Email,
+    Regex(LitStr),
+    OneOf(Vec<Expr>),
+    Nested,
+}
+
+// `min = ..` / `max = ..` inside `length(...)` and `range(...)`
+fn bounds(meta: &syn::meta::ParseNestedMeta) -> syn::Result<(Option<Expr>, Option<Expr>)> {
+    let (mut min, mut max) = (None, None);
+    meta.parse_nested_meta(|bound| {
+        if bound.path.is_ident("min") {
+            min = Some(bound.value()?.parse()?);
+        } else if bound.path.is_ident("max") {
+            max = Some(bound.value()?.parse()?);
+        } else {
+            return Err(bound.error("expected `min` or `max`"));
+        }
+        Ok(())
+    })?;
+    Ok((min, max))
+}
+
+fn parse(field: &Field) -> syn::Result<Vec<Check>> {
+    let mut checks = vec![];
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("validate"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("length") {
+                let (min, max) = bounds(&meta)?;
+                checks.push(Check::Length { min, max });
+            } else if meta.path.is_ident("range") {
+                let (min, max) = bounds(&meta)?;
+                checks.push(Check::Range { min, max });
+            } else if meta.path.is_ident("email") {
+                checks.push(Check::Email);
+            } else if meta.path.is_ident("regex") {
+                let pattern: LitStr = meta.value()?.parse()?;
+                // a bad pattern is a compile error rather than a failed check at runtime
+                if let Err(err) = regex::Regex::new(&pattern.value()) {
+                    return Err(syn::Error::new_spanned(
+                        &pattern,
+                        format!("invalid regex: {err}"),
+                    ));
+                }
+                checks.push(Check::Regex(pattern));
+            } else if meta.path.is_ident("one_of") {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let options = Punctuated::<Expr, Token![,]>::parse_terminated(&content)?;
+                checks.push(Check::OneOf(options.into_iter().collect()));
+            } else if meta.path.is_ident("nested") {
+                checks.push(Check::Nested);
+            } else {
+                return Err(meta.error("unsupported validate attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(checks)
+}
+
+fn optional(bound: Option<&Expr>) -> TokenStream {
+    bound.map_or_else(|| quote!(None), |bound| quote!(Some(#bound)))
+}
+
+fn is_option(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(|segment| segment.ident == "Option"))
+}
+
+// statements pushing into a `ValidationErrors` named `errors`, `Option` fields are only checked when set
+pub fn checks(input: &DeriveInput, fields: &[&Field]) -> syn::Result<Vec<TokenStream>> {
+    let mut statements = vec![];
+    for field in fields {
+        let checks = parse(field)?;
+        if checks.is_empty() {
+            continue;
+        }
+        let ident = &field.ident;
+        let key = attr::serde_name(input, field)?;
+        let calls = checks.iter().map(|check| match check {
+            Check::Length { min, max } => {
+                let (min, max) = (optional(min.as_ref()), optional(max.as_ref()));
+                quote!(::mongoose::validation::length(&mut errors, #key, value, #min, #max);)
+            }
+            Check::Range { min, max } => {
+                let (min, max) = (optional(min.as_ref()), optional(max.as_ref()));
+                quote!(::mongoose::validation::range(&mut errors, #key, value, #min, #max);)
+            }
+            Check::Email => quote!(::mongoose::validation::email(&mut errors, #key, value);),
+            Check::Regex(pattern) => {
+                quote!(::mongoose::validation::regex(&mut errors, #key, value, #pattern);)
+            }
+            Check::OneOf(options) => {
+                quote!(::mongoose::validation::one_of(&mut errors, #key, value, &[#(#options),*]);)
+            }
+            Check::Nested => quote! {
+                errors.nest(#key, ::mongoose::validation::Validate::validate(value));
+            },
+        });
+        statements.push(if is_option(&field.ty) {
+            quote! {
+                if let Some(value) = &self.#ident {
+                    #(#calls)*
+                }
+            }
+        } else {
+            quote! {
+                {
+                    let value = &self.#ident;
+                    #(#calls)*
+                }
+            }
+        });
+    }
+    Ok(statements)
+}
+
+pub fn validate_fn(checks: &[TokenStream]) -> TokenStream {
+    quote! {
+        fn validate(&self) -> Result<(), ::mongoose::validation::ValidationErrors> {
+            let mut errors = ::mongoose::validation::ValidationErrors::new();
+            #(#checks)*
+            errors.into_result()
+        }
+    }
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = attr::named_fields(input, "Validate")?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let validate = validate_fn(&checks(input, &fields)?);
+    Ok(quote! {
+        impl #impl_generics ::mongoose::validation::Validate for #ident #ty_generics #where_clause {
+            #validate
+        }
+    })
+}
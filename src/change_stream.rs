@@ -0,0 +1,110 @@
+use crate::types::MongooseError;
+use bson::Document;
+use futures::Stream;
+use mongodb::change_stream::{
+    event::{ChangeStreamEvent, OperationType, ResumeToken},
+    ChangeStream as DriverChangeStream,
+};
+use serde::de::DeserializeOwned;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[derive(Debug, Clone)]
+pub enum ChangeEvent<T> {
+    Insert {
+        document_key: Document,
+        document: T,
+    },
+    Update {
+        document_key: Document,
+        updated_fields: Document,
+        removed_fields: Vec<String>,
+        // only set when watching with `full_document` enabled
+        document: Option<T>,
+    },
+    Replace {
+        document_key: Document,
+        document: T,
+    },
+    Delete {
+        document_key: Document,
+    },
+    // the stream can no longer be resumed, e.g. the collection was dropped
+    Invalidate,
+    Other(OperationType),
+}
+
+impl<T> TryFrom<ChangeStreamEvent<T>> for ChangeEvent<T> {
+    type Error = MongooseError;
+
+    fn try_from(event: ChangeStreamEvent<T>) -> Result<Self, Self::Error> {
+        let document_key = event.document_key.unwrap_or_default();
+        let missing = |field: &str| {
+            MongooseError::Watch(
+                format!("{:?} event is missing {field}", event.operation_type).into(),
+            )
+        };
+        let change = match event.operation_type {
+            OperationType::Insert => Self::Insert {
+                document_key,
+                document: event.full_document.ok_or_else(|| missing("fullDocument"))?,
+            },
+            OperationType::Update => {
+                let description = event
+                    .update_description
+                    .ok_or_else(|| missing("updateDescription"))?;
+                Self::Update {
+                    document_key,
+                    updated_fields: description.updated_fields,
+                    removed_fields: description.removed_fields,
+                    document: event.full_document,
+                }
+            }
+            OperationType::Replace => Self::Replace {
+                document_key,
+                document: event.full_document.ok_or_else(|| missing("fullDocument"))?,
+            },
+            OperationType::Delete => Self::Delete { document_key },
+            OperationType::Invalidate => Self::Invalidate,
+            other => Self::Other(other),
+        };
+        Ok(change)
+    }
+}
+
+pub struct ChangeStream<T: DeserializeOwned> {
+    inner: DriverChangeStream<ChangeStreamEvent<T>>,
+}
+
+impl<T: DeserializeOwned> ChangeStream<T> {
+    pub(crate) const fn new(inner: DriverChangeStream<ChangeStreamEvent<T>>) -> Self {
+        Self { inner }
+    }
+
+    // persist this and pass it to `ChangeStreamOptions::resume_after` to pick up where the stream left off
+    pub fn resume_token(&self) -> Option<ResumeToken> {
+        self.inner.resume_token()
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.inner.is_alive()
+    }
+}
+
+impl<T: DeserializeOwned + Unpin> Stream for ChangeStream<T> {
+    type Item = Result<ChangeEvent<T>, MongooseError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().inner)
+            .poll_next(cx)
+            .map(|event| {
+                event.map(|event| {
+                    event
+                        .map_err(MongooseError::watch)
+                        .and_then(ChangeEvent::try_from)
+                })
+            })
+    }
+}
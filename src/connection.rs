@@ -1,7 +1,12 @@
-use async_once::AsyncOnce;
+use crate::{tenant, types::MongooseError};
 use lazy_static::lazy_static;
 use mongodb::{options::ClientOptions, Client, Database};
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
 
+#[derive(Debug, Clone)]
 pub struct Connection {
     pub database: Database,
     pub client: Client,
@@ -10,30 +15,114 @@ pub struct Connection {
 const LOCAL_URI: &str =
     "mongodb://localhost:27017/mongoose-rs-local?connectTimeoutMS=10000&maxPoolSize=500";
 
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub uri: String,
+    // overrides the default database from the connection string
+    pub database: Option<String>,
+}
+
+impl Config {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            database: None,
+        }
+    }
+
+    // reads `MONGO_URI`, falling back to a local instance
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("MONGO_URI").unwrap_or_else(|_| LOCAL_URI.to_string()))
+    }
+
+    #[must_use]
+    pub fn database(mut self, database: impl Into<String>) -> Self {
+        self.database = Some(database.into());
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+impl Connection {
+    pub const fn new(client: Client, database: Database) -> Self {
+        Self { database, client }
+    }
+
+    pub async fn connect(config: Config) -> Result<Self, MongooseError> {
+        let client_options = ClientOptions::parse(&config.uri)
+            .await
+            .map_err(MongooseError::connection)?;
+        let client = Client::with_options(client_options).map_err(MongooseError::connection)?;
+        let database = match config.database {
+            Some(name) => client.database(&name),
+            None => client
+                .default_database()
+                .ok_or_else(|| MongooseError::Connection("no default database found".into()))?,
+        };
+        Ok(Self { database, client })
+    }
+}
+
+pub const DEFAULT_CONNECTION: &str = "default";
+
 lazy_static! {
-    pub static ref POOL: AsyncOnce<Connection> = AsyncOnce::new(async {
-        let mongo_uri = std::env::var("MONGO_URI").map_or(LOCAL_URI.to_string(), |uri| uri);
-        let client_options = ClientOptions::parse(mongo_uri).await.map_or_else(
-            |err| {
-                tracing::error!("error parsing client options {:?}", err);
-                std::process::exit(1);
-            },
-            |opts| opts,
-        );
-        let client = Client::with_options(client_options).map_or_else(
-            |err| {
-                tracing::error!("error connecting client: {:?}", err);
-                std::process::exit(1);
-            },
-            |client| client,
-        );
-        let database = client.default_database().map_or_else(
-            || {
-                tracing::error!("no default database found");
-                std::process::exit(1);
-            },
-            |db| db,
-        );
-        Connection { database, client }
-    });
+    static ref POOL: RwLock<HashMap<String, Connection>> = RwLock::new(HashMap::new());
+}
+
+// connects using `config` and installs it as the default connection
+pub async fn init(config: Config) -> Result<(), MongooseError> {
+    init_named(DEFAULT_CONNECTION, config).await
+}
+
+// connects using `config` and registers it under `name`
+pub async fn init_named(name: impl Into<String>, config: Config) -> Result<(), MongooseError> {
+    let connection = Connection::connect(config).await?;
+    set_named(name, connection);
+    Ok(())
+}
+
+// installs an already built connection as the default, replacing any previous one
+pub fn set(connection: Connection) {
+    set_named(DEFAULT_CONNECTION, connection);
+}
+
+pub fn set_named(name: impl Into<String>, connection: Connection) {
+    let name = name.into();
+    POOL.write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(name.clone(), connection);
+    // after releasing the pool lock
+    tenant::evict(&name);
+}
+
+pub fn is_initialized() -> bool {
+    is_registered(DEFAULT_CONNECTION)
+}
+
+pub fn is_registered(name: &str) -> bool {
+    POOL.read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .contains_key(name)
+}
+
+pub fn get() -> Result<Connection, MongooseError> {
+    get_named(DEFAULT_CONNECTION)
+}
+
+pub fn get_named(name: &str) -> Result<Connection, MongooseError> {
+    POOL.read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(name)
+        .cloned()
+        .ok_or_else(|| {
+            MongooseError::Connection(
+                format!("no connection named {name:?} initialized, call mongoose::init first")
+                    .into(),
+            )
+        })
 }
@@ -0,0 +1,74 @@
+use bson::Bson;
+use std::fmt::Display;
+
+// implemented by `#[derive(Fields)]`, `paths` gives a nested struct its paths under a parent
+pub trait Fields {
+    type Paths;
+
+    fn paths(prefix: &str) -> Self::Paths;
+}
+
+// a dotted field path as stored in the database, e.g. `address.city`
+//
+// doc! { (User::fields().address().city()): "Denver" }
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FieldPath(String);
+
+impl FieldPath {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self(path.into())
+    }
+
+    // `key` under `prefix`, the root when `prefix` is empty
+    pub fn under(prefix: &str, key: &str) -> Self {
+        if prefix.is_empty() {
+            Self::new(key)
+        } else {
+            Self(format!("{prefix}.{key}"))
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // a path below this one, e.g. an array index or the positional `$`
+    #[must_use]
+    pub fn join(&self, key: impl Display) -> Self {
+        Self(format!("{}.{key}", self.0))
+    }
+}
+
+impl AsRef<str> for FieldPath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<FieldPath> for String {
+    fn from(path: FieldPath) -> Self {
+        path.0
+    }
+}
+
+impl From<&FieldPath> for String {
+    fn from(path: &FieldPath) -> Self {
+        path.0.clone()
+    }
+}
+
+// as a value, e.g. the target of `$rename` or a `$` field reference
+impl From<FieldPath> for Bson {
+    fn from(path: FieldPath) -> Self {
+        Self::String(path.0)
+    }
+}
+
+#[cfg(feature = "derive")]
+pub use mongoose_derive::Fields;
@@ -0,0 +1,129 @@
+use bson::{Bson, Document};
+use mongodb::IndexModel;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct SyncIndexOptions {
+    // report what would change without touching the collection
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexSyncReport {
+    pub created: Vec<String>,
+    // declared with different keys or options, dropped and created again
+    pub changed: Vec<String>,
+    pub dropped: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub dry_run: bool,
+}
+
+impl IndexSyncReport {
+    pub fn is_in_sync(&self) -> bool {
+        self.created.is_empty() && self.changed.is_empty() && self.dropped.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IndexPlan {
+    pub create: Vec<IndexModel>,
+    pub drop: Vec<String>,
+    pub report: IndexSyncReport,
+}
+
+// the server's default name, `{ email: 1, created_at: -1 }` -> `email_1_created_at_-1`
+pub fn index_name(index: &IndexModel) -> String {
+    if let Some(name) = index
+        .options
+        .as_ref()
+        .and_then(|options| options.name.clone())
+    {
+        return name;
+    }
+    index
+        .keys
+        .iter()
+        .map(|(key, value)| match value {
+            // avoid the quotes `Bson`'s display adds to strings
+            Bson::String(value) => format!("{key}_{value}"),
+            value => format!("{key}_{value}"),
+        })
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn normalize_keys(keys: &Document) -> Vec<(String, Bson)> {
+    keys.iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Bson::Int32(value) => Bson::Double(f64::from(*value)),
+                #[allow(clippy::cast_precision_loss)]
+                Bson::Int64(value) => Bson::Double(*value as f64),
+                value => value.clone(),
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+fn same_keys(declared: &Document, existing: &Document) -> bool {
+    // text indexes are stored as `{ _fts: "text", _ftsx: 1 }`
+    let declared_text = declared
+        .values()
+        .any(|value| value.as_str() == Some("text"));
+    if declared_text && existing.contains_key("_fts") {
+        return true;
+    }
+    normalize_keys(declared) == normalize_keys(existing)
+}
+
+fn same_options(declared: &IndexModel, existing: &IndexModel) -> bool {
+    let declared = declared.options.clone().unwrap_or_default();
+    let existing = existing.options.clone().unwrap_or_default();
+    declared.unique.unwrap_or_default() == existing.unique.unwrap_or_default()
+        && declared.sparse.unwrap_or_default() == existing.sparse.unwrap_or_default()
+        && declared.expire_after == existing.expire_after
+        && declared.partial_filter_expression == existing.partial_filter_expression
+}
+
+// diffs declared indexes against the collection, `_id_` is never touched
+pub(crate) fn plan(declared: &[IndexModel], existing: &[IndexModel], dry_run: bool) -> IndexPlan {
+    let mut plan = IndexPlan {
+        report: IndexSyncReport {
+            dry_run,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    for index in declared {
+        let name = index_name(index);
+        match existing
+            .iter()
+            .find(|existing| index_name(existing) == name)
+        {
+            None => {
+                plan.report.created.push(name);
+                plan.create.push(index.clone());
+            }
+            Some(current)
+                if same_keys(&index.keys, &current.keys) && same_options(index, current) =>
+            {
+                plan.report.unchanged.push(name);
+            }
+            Some(_) => {
+                plan.drop.push(name.clone());
+                plan.report.changed.push(name);
+                plan.create.push(index.clone());
+            }
+        }
+    }
+    let declared_names = declared.iter().map(index_name).collect::<Vec<_>>();
+    for index in existing {
+        let name = index_name(index);
+        if name != "_id_" && !declared_names.contains(&name) {
+            plan.drop.push(name.clone());
+            plan.report.dropped.push(name);
+        }
+    }
+    plan
+}
@@ -0,0 +1,120 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Naming {
+    // english plural of the snake cased type name, `Category` -> `categories`
+    Inflected,
+    // the default, appends `s` unless the name already ends in one, `Category` -> `categorys`
+    #[default]
+    Legacy,
+}
+
+const UNCOUNTABLE: &[&str] = &[
+    "data",
+    "deer",
+    "equipment",
+    "feedback",
+    "fish",
+    "information",
+    "jeans",
+    "media",
+    "metadata",
+    "money",
+    "moose",
+    "news",
+    "police",
+    "rice",
+    "series",
+    "sheep",
+    "software",
+    "species",
+];
+
+const IRREGULAR: &[(&str, &str)] = &[
+    ("alias", "aliases"),
+    ("axis", "axes"),
+    ("child", "children"),
+    ("criterion", "criteria"),
+    ("datum", "data"),
+    ("echo", "echoes"),
+    ("foot", "feet"),
+    ("goose", "geese"),
+    ("hero", "heroes"),
+    ("index", "indices"),
+    ("louse", "lice"),
+    ("man", "men"),
+    ("matrix", "matrices"),
+    ("medium", "media"),
+    ("mouse", "mice"),
+    ("ox", "oxen"),
+    ("person", "people"),
+    ("potato", "potatoes"),
+    ("quiz", "quizzes"),
+    ("tomato", "tomatoes"),
+    ("tooth", "teeth"),
+    ("vertex", "vertices"),
+    ("veto", "vetoes"),
+    ("woman", "women"),
+];
+
+// the `-fe` words with a `-ves` plural, `cafe` and `safe` just take an `s`
+const FE_TO_VES: &[&str] = &["knife", "life", "wife"];
+
+// pluralizes the last `_` separated word of a snake cased name
+pub fn pluralize(name: &str) -> String {
+    let (prefix, word) = name
+        .rfind('_')
+        .map_or(("", name), |index| name.split_at(index + 1));
+    format!("{prefix}{}", pluralize_word(word))
+}
+
+fn pluralize_word(word: &str) -> String {
+    if word.is_empty() || UNCOUNTABLE.contains(&word) {
+        return word.to_string();
+    }
+    if let Some((_, plural)) = IRREGULAR.iter().find(|(singular, _)| *singular == word) {
+        return (*plural).to_string();
+    }
+    if IRREGULAR.iter().any(|(_, plural)| *plural == word) {
+        return word.to_string();
+    }
+    if let Some(stem) = word.strip_suffix("sis") {
+        return format!("{stem}ses");
+    }
+    if word.ends_with("us") {
+        return format!("{word}es");
+    }
+    if ["ss", "x", "ch", "sh", "z"]
+        .iter()
+        .any(|suffix| word.ends_with(suffix))
+    {
+        return format!("{word}es");
+    }
+    if word.ends_with('s') {
+        // assume it is already plural
+        return word.to_string();
+    }
+    if let Some(stem) = word.strip_suffix('y') {
+        let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u');
+        if !stem.ends_with(is_vowel) || stem.ends_with("qu") {
+            return format!("{stem}ies");
+        }
+    }
+    if let Some(stem) = word.strip_suffix("fe") {
+        if FE_TO_VES.iter().any(|singular| word.ends_with(singular)) {
+            return format!("{stem}ves");
+        }
+    }
+    if let Some(stem) = word.strip_suffix('f') {
+        if stem.ends_with('l') || stem.ends_with('r') {
+            return format!("{stem}ves");
+        }
+    }
+    format!("{word}s")
+}
+
+pub fn legacy_pluralize(name: &str) -> String {
+    let mut name = name.to_string();
+    if !name.ends_with('s') {
+        name.push('s');
+    }
+    name
+}
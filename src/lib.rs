@@ -1,8 +1,15 @@
+// lets derive output refer to `::mongoose` from inside this crate
+extern crate self as mongoose;
+
 // expose 3rd party crates
-pub use bson::doc;
+pub use bson::{doc, Document};
 pub use mongodb::{
     bson::Regex,
-    options::{AggregateOptions, IndexOptions},
+    change_stream::event::ResumeToken,
+    options::{
+        AggregateOptions, ChangeStreamOptions, FullDocumentType, IndexOptions, ValidationAction,
+        ValidationLevel,
+    },
     IndexModel,
 };
 
@@ -13,13 +20,38 @@ pub use bson::uuid::Uuid;
 pub use bson::{serde_helpers::chrono_datetime_as_bson_datetime as TimestampSerializer, DateTime};
 
 // expose crates
+pub mod change_stream;
 pub mod connection;
+pub mod fields;
+pub mod indexes;
+pub mod inflection;
+pub mod migrations;
+pub mod pagination;
+pub mod query;
+pub mod schema;
+pub mod tenant;
+pub mod transaction;
 pub mod types;
+pub mod update;
+pub mod validation;
+pub use connection::{init, init_named, Config};
+pub use mongodb::ClientSession;
+pub use query::{Filter, Query};
+pub use transaction::{transaction, transaction_on};
+pub use update::Update;
 
 // expose model
 mod model;
 pub use model::Model;
+#[cfg(feature = "derive")]
+pub use mongoose_derive::{BsonSchema, Fields, Model, Validate};
 
 // tests
 #[cfg(test)]
+#[allow(
+    clippy::useless_conversion,
+    clippy::len_zero,
+    clippy::op_ref,
+    clippy::bool_assert_comparison
+)]
 mod tests;
@@ -0,0 +1,444 @@
+use crate::{connection, types::MongooseError};
+use bson::{doc, oid::ObjectId, DateTime, Document};
+use futures::{
+    future::{self, BoxFuture, Either},
+    TryStreamExt,
+};
+use mongodb::{
+    error::{ErrorKind, WriteFailure},
+    options::{FindOptions, UpdateOptions},
+    Collection, Database,
+};
+use serde::{Deserialize, Serialize};
+use std::{future::Future, pin::pin, time::Duration};
+
+const COLLECTION: &str = "_migrations";
+const LOCK_ID: &str = "__lock__";
+
+type MigrationFn =
+    Box<dyn Fn(Database) -> BoxFuture<'static, Result<(), MongooseError>> + Send + Sync>;
+
+// the checksum only covers the id, description and `source`, the closures can not be hashed
+// set `source` so edits to an applied migration are caught instead of silently ignored
+pub struct Migration {
+    id: String,
+    description: String,
+    source: String,
+    up: MigrationFn,
+    down: Option<MigrationFn>,
+}
+
+fn boxed<F, Fut>(migration: F) -> MigrationFn
+where
+    F: Fn(Database) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), MongooseError>> + Send + 'static,
+{
+    Box::new(move |database| Box::pin(migration(database)))
+}
+
+impl Migration {
+    // Migration::new("0001_lowercase_emails", |db| async move { ... Ok(()) })
+    //     .source(include_str!("migrations/0001_lowercase_emails.rs"))
+    pub fn new<F, Fut>(id: impl Into<String>, up: F) -> Self
+    where
+        F: Fn(Database) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), MongooseError>> + Send + 'static,
+    {
+        Self {
+            id: id.into(),
+            description: String::new(),
+            source: String::new(),
+            up: boxed(up),
+            down: None,
+        }
+    }
+
+    #[must_use]
+    pub fn down<F, Fut>(mut self, down: F) -> Self
+    where
+        F: Fn(Database) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), MongooseError>> + Send + 'static,
+    {
+        self.down = Some(boxed(down));
+        self
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    // feeds the checksum, e.g. `include_str!` of the file defining the migration
+    // without it, changing the body of an applied migration goes unnoticed
+    #[must_use]
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // 64 bit FNV-1a of id, description and source, stable across rust versions unlike
+    // `DefaultHasher`
+    pub fn checksum(&self) -> String {
+        let hash = [&self.id, &self.description, &self.source]
+            .iter()
+            .flat_map(|part| part.bytes().chain(std::iter::once(0)))
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+            });
+        format!("{hash:016x}")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub description: String,
+    pub checksum: String,
+    pub applied_at: DateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub id: String,
+    pub checksum: String,
+    // `None` while pending
+    pub applied_at: Option<DateTime>,
+}
+
+pub struct Migrator {
+    migrations: Vec<Migration>,
+    connection: String,
+    database: Option<String>,
+    lock_ttl: Duration,
+    lock_timeout: Duration,
+}
+
+impl Default for Migrator {
+    fn default() -> Self {
+        Self {
+            migrations: vec![],
+            connection: connection::DEFAULT_CONNECTION.to_string(),
+            database: None,
+            lock_ttl: Duration::from_secs(10 * 60),
+            lock_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Migrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // migrations run in the order they are added
+    #[must_use]
+    pub fn register(mut self, migration: Migration) -> Self {
+        self.migrations.push(migration);
+        self
+    }
+
+    #[must_use]
+    pub fn connection(mut self, name: impl Into<String>) -> Self {
+        self.connection = name.into();
+        self
+    }
+
+    // defaults to the connection's default database
+    #[must_use]
+    pub fn database(mut self, name: impl Into<String>) -> Self {
+        self.database = Some(name.into());
+        self
+    }
+
+    // a lock not renewed for this long is considered abandoned by a crashed instance
+    // the holder renews it every third of the ttl while migrations run
+    #[must_use]
+    pub const fn lock_ttl(mut self, ttl: Duration) -> Self {
+        self.lock_ttl = ttl;
+        self
+    }
+
+    // how long to wait for another instance to finish migrating
+    #[must_use]
+    pub const fn lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    fn get_database(&self) -> Result<Database, MongooseError> {
+        let connection = connection::get_named(&self.connection)?;
+        Ok(self
+            .database
+            .as_ref()
+            .map_or(connection.database, |name| connection.client.database(name)))
+    }
+
+    fn records(database: &Database) -> Collection<AppliedMigration> {
+        database.collection(COLLECTION)
+    }
+
+    pub async fn applied(&self) -> Result<Vec<AppliedMigration>, MongooseError> {
+        let database = self.get_database()?;
+        Self::records(&database)
+            .find(
+                doc! { "_id": { "$ne": LOCK_ID } },
+                FindOptions::builder()
+                    .sort(doc! { "applied_at": 1, "_id": 1 })
+                    .build(),
+            )
+            .await
+            .map_err(MongooseError::migration)?
+            .try_collect()
+            .await
+            .map_err(MongooseError::migration)
+    }
+
+    pub async fn status(&self) -> Result<Vec<MigrationStatus>, MongooseError> {
+        let applied = self.applied().await?;
+        Ok(self
+            .migrations
+            .iter()
+            .map(|migration| MigrationStatus {
+                id: migration.id.clone(),
+                checksum: migration.checksum(),
+                applied_at: applied
+                    .iter()
+                    .find(|record| record.id == migration.id)
+                    .map(|record| record.applied_at),
+            })
+            .collect())
+    }
+
+    pub async fn pending(&self) -> Result<Vec<String>, MongooseError> {
+        Ok(self
+            .status()
+            .await?
+            .into_iter()
+            .filter(|status| status.applied_at.is_none())
+            .map(|status| status.id)
+            .collect())
+    }
+
+    // applies every pending migration, returning the ids that ran
+    pub async fn up(&self) -> Result<Vec<String>, MongooseError> {
+        let database = self.get_database()?;
+        self.locked(&database, self.run_up(&database)).await
+    }
+
+    // rolls back the last `steps` applied migrations, newest first
+    pub async fn down(&self, steps: usize) -> Result<Vec<String>, MongooseError> {
+        let database = self.get_database()?;
+        self.locked(&database, self.run_down(&database, steps))
+            .await
+    }
+
+    // runs `work` holding the lock, a lost lock stops `work` at its next await point
+    async fn locked<T>(
+        &self,
+        database: &Database,
+        work: impl Future<Output = Result<T, MongooseError>>,
+    ) -> Result<T, MongooseError> {
+        let owner = self.lock(database).await?;
+        let result = match future::select(pin!(work), pin!(self.heartbeat(database, &owner))).await
+        {
+            Either::Left((result, _)) => result,
+            Either::Right((err, _)) => Err(err),
+        };
+        let unlocked = self.unlock(database, &owner).await;
+        // the migration's own failure matters more than a failed unlock
+        match (result, unlocked) {
+            (Ok(value), unlocked) => unlocked.map(|()| value),
+            (Err(err), Ok(())) => Err(err),
+            (Err(err), Err(unlock_err)) => {
+                tracing::error!("error releasing migration lock: {:?}", unlock_err);
+                Err(err)
+            }
+        }
+    }
+
+    fn expires_at(&self) -> DateTime {
+        let ttl = i64::try_from(self.lock_ttl.as_millis()).unwrap_or(i64::MAX);
+        DateTime::from_millis(DateTime::now().timestamp_millis().saturating_add(ttl))
+    }
+
+    // keeps pushing `expires_at` forward, only returns once the lock is lost
+    async fn heartbeat(&self, database: &Database, owner: &str) -> MongooseError {
+        let locks = database.collection::<Document>(COLLECTION);
+        loop {
+            tokio::time::sleep(self.lock_ttl / 3).await;
+            let renewed = locks
+                .update_one(
+                    doc! { "_id": LOCK_ID, "owner": owner, "locked": true },
+                    doc! { "$set": { "expires_at": self.expires_at() } },
+                    None,
+                )
+                .await;
+            match renewed {
+                Ok(result) if result.matched_count == 0 => {
+                    return MongooseError::Migration(
+                        "migration lock was taken over by another instance".into(),
+                    );
+                }
+                Ok(_) => {}
+                // a later beat may still get through before the lock expires
+                Err(err) => tracing::warn!("error renewing migration lock: {:?}", err),
+            }
+        }
+    }
+
+    fn verify(&self, applied: &[AppliedMigration]) -> Result<(), MongooseError> {
+        let mut seen_pending = None;
+        for migration in &self.migrations {
+            if self
+                .migrations
+                .iter()
+                .filter(|other| other.id == migration.id)
+                .count()
+                > 1
+            {
+                return Err(MongooseError::Migration(
+                    format!("migration {:?} is registered more than once", migration.id).into(),
+                ));
+            }
+            match applied.iter().find(|record| record.id == migration.id) {
+                Some(record) if record.checksum != migration.checksum() => {
+                    return Err(MongooseError::Migration(
+                        format!("checksum mismatch for applied migration {:?}", migration.id)
+                            .into(),
+                    ));
+                }
+                Some(_) => {
+                    if let Some(pending) = seen_pending {
+                        return Err(MongooseError::Migration(
+                            format!(
+                            "migration {pending:?} is pending but later migration {:?} is applied",
+                            migration.id
+                        )
+                            .into(),
+                        ));
+                    }
+                }
+                None => seen_pending = seen_pending.or(Some(&migration.id)),
+            }
+        }
+        Ok(())
+    }
+
+    async fn run_up(&self, database: &Database) -> Result<Vec<String>, MongooseError> {
+        let applied = self.applied().await?;
+        self.verify(&applied)?;
+        let mut ran = vec![];
+        for migration in &self.migrations {
+            if applied.iter().any(|record| record.id == migration.id) {
+                continue;
+            }
+            tracing::info!("applying migration {:?}", migration.id);
+            (migration.up)(database.clone()).await?;
+            Self::records(database)
+                .insert_one(
+                    AppliedMigration {
+                        id: migration.id.clone(),
+                        description: migration.description.clone(),
+                        checksum: migration.checksum(),
+                        applied_at: DateTime::now(),
+                    },
+                    None,
+                )
+                .await
+                .map_err(MongooseError::migration)?;
+            ran.push(migration.id.clone());
+        }
+        Ok(ran)
+    }
+
+    async fn run_down(
+        &self,
+        database: &Database,
+        steps: usize,
+    ) -> Result<Vec<String>, MongooseError> {
+        let applied = self.applied().await?;
+        self.verify(&applied)?;
+        let mut reverted = vec![];
+        for record in applied.iter().rev().take(steps) {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|migration| migration.id == record.id)
+                .ok_or_else(|| {
+                    MongooseError::Migration(
+                        format!("applied migration {:?} is not registered", record.id).into(),
+                    )
+                })?;
+            let down = migration.down.as_ref().ok_or_else(|| {
+                MongooseError::Migration(format!("migration {:?} has no down", record.id).into())
+            })?;
+            tracing::info!("reverting migration {:?}", migration.id);
+            down(database.clone()).await?;
+            Self::records(database)
+                .delete_one(doc! { "_id": &record.id }, None)
+                .await
+                .map_err(MongooseError::migration)?;
+            reverted.push(record.id.clone());
+        }
+        Ok(reverted)
+    }
+
+    // takes the lock document, waiting up to `lock_timeout` for another holder
+    async fn lock(&self, database: &Database) -> Result<String, MongooseError> {
+        let locks = database.collection::<Document>(COLLECTION);
+        let owner = ObjectId::new().to_hex();
+        let started = std::time::Instant::now();
+        loop {
+            let now = DateTime::now();
+            let expires_at = self.expires_at();
+            let acquired = locks
+                .update_one(
+                    doc! {
+                        "_id": LOCK_ID,
+                        "$or": [{ "locked": false }, { "expires_at": { "$lt": now } }],
+                    },
+                    doc! { "$set": { "locked": true, "owner": &owner, "expires_at": expires_at } },
+                    UpdateOptions::builder().upsert(true).build(),
+                )
+                .await;
+            match acquired {
+                Ok(_) => return Ok(owner),
+                // the upsert collides with a lock held by someone else
+                Err(err)
+                    if matches!(
+                        *err.kind,
+                        ErrorKind::Write(WriteFailure::WriteError(ref write)) if write.code == 11000
+                    ) =>
+                {
+                    if started.elapsed() >= self.lock_timeout {
+                        return Err(MongooseError::Migration(
+                            "timed out waiting for the migration lock".into(),
+                        ));
+                    }
+                    tracing::debug!("migration lock is held, retrying");
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+                Err(err) => return Err(MongooseError::migration(err)),
+            }
+        }
+    }
+
+    async fn unlock(&self, database: &Database, owner: &str) -> Result<(), MongooseError> {
+        database
+            .collection::<Document>(COLLECTION)
+            .update_one(
+                doc! { "_id": LOCK_ID, "owner": owner },
+                doc! { "$set": { "locked": false } },
+                None,
+            )
+            .await
+            .map_err(MongooseError::migration)?;
+        Ok(())
+    }
+}
@@ -1,36 +1,135 @@
 use crate::{
-    connection::POOL,
-    types::{ListOptions, MongooseError},
+    change_stream::ChangeStream,
+    connection,
+    indexes::{self, IndexSyncReport, SyncIndexOptions},
+    inflection::{self, Naming},
+    pagination::{self, Cursor, Direction, Facet, KeysetOptions, KeysetPage, Page},
+    query::Query,
+    schema::{BsonSchema, SchemaReport},
+    tenant::{self, Tenancy},
+    types::{LenientList, ListOptions, MongooseError},
+    update,
+    validation::ValidationErrors,
 };
 use bson::{doc, Document};
-use futures::StreamExt;
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 use mongodb::{
+    error::ErrorKind,
     options::{
-        AggregateOptions, CreateCollectionOptions, FindOneAndUpdateOptions, FindOptions,
-        ReturnDocument,
+        AggregateOptions, ChangeStreamOptions, CreateCollectionOptions, FindOneAndUpdateOptions,
+        FindOneOptions, FindOptions, ReturnDocument, ValidationAction, ValidationLevel,
     },
     results::{CreateIndexesResult, DeleteResult, InsertManyResult, UpdateResult},
-    Client, Collection, Database, IndexModel,
+    Client, ClientSession, Collection, Database, IndexModel,
 };
 use serde::{de::DeserializeOwned, Serialize};
 
+// the write already went through, so a failing post hook must not look like a failed write
+fn committed(hook: Result<(), MongooseError>) {
+    if let Err(err) = hook {
+        tracing::error!("post hook failed after a committed write: {:?}", err);
+    }
+}
+
+// NamespaceNotFound, the collection has not been created yet
+fn is_namespace_not_found(err: &mongodb::error::Error) -> bool {
+    matches!(*err.kind, ErrorKind::Command(ref command) if command.code == 26)
+}
+
+// `pre_save` and validation of a model about to be inserted, plus the document carrying its
+// tenant for field scoped models
+async fn prepare_insert<M: Model>(model: &M) -> Result<(M, Option<Document>), MongooseError> {
+    let mut model = model.clone();
+    model.pre_save().await?;
+    model.validate().map_err(MongooseError::Validation)?;
+    let document = tenant::stamp(&model)?;
+    Ok((model, document))
+}
+
+// the same for a batch, validation errors are reported under the position of the document,
+// e.g. `2.email`
+async fn prepare_inserts<M: Model>(
+    docs: &[M],
+) -> Result<(Vec<M>, Option<Vec<Document>>), MongooseError> {
+    let mut models = docs.to_vec();
+    let mut errors = ValidationErrors::new();
+    for (index, model) in models.iter_mut().enumerate() {
+        model.pre_save().await?;
+        errors.nest(&index.to_string(), model.validate());
+    }
+    errors.into_result().map_err(MongooseError::Validation)?;
+    let documents = if matches!(M::tenancy(), Tenancy::Field(_)) {
+        let mut documents = Vec::with_capacity(models.len());
+        for model in &models {
+            documents.extend(tenant::stamp(model)?);
+        }
+        Some(documents)
+    } else {
+        None
+    };
+    Ok((models, documents))
+}
+
 #[allow(async_fn_in_trait)]
 pub trait Model
 where
-    Self: Serialize + DeserializeOwned + Unpin + Sync + Sized + Send + Default + Clone,
+    Self: Serialize + DeserializeOwned + Unpin + Sync + Sized + Send + Default + Clone + 'static,
 {
-    async fn client() -> &'static Client {
-        &POOL.get().await.client
+    // explicit collection name, skips the naming convention entirely
+    const COLLECTION_NAME: Option<&'static str> = None;
+    // how the collection name is derived from the type name
+    // `Naming::Legacy` by default so existing collections keep their names, opt in to
+    // `Naming::Inflected` for new models
+    const NAMING: Naming = Naming::Legacy;
+
+    // name of the registered connection this model lives on
+    fn connection_name() -> &'static str {
+        connection::DEFAULT_CONNECTION
+    }
+    // overrides the connection's default database for this model
+    fn database_name() -> Option<&'static str> {
+        None
     }
-    async fn database() -> &'static Database {
-        &POOL.get().await.database
+    // opt in to resolving the database from the current tenant scope
+    fn tenancy() -> Tenancy {
+        Tenancy::None
     }
-    async fn collection() -> Collection<Self> {
-        POOL.get().await.database.collection::<Self>(&Self::name())
+    // database holding a tenant's data for `Tenancy::Database` models
+    // the prefix keeps tenants apart from system and application databases
+    fn tenant_database(tenant: &str) -> String {
+        format!("tenant_{tenant}")
+    }
+
+    async fn client() -> Result<Client, MongooseError> {
+        Ok(connection::get_named(Self::connection_name())?.client)
+    }
+    async fn database() -> Result<Database, MongooseError> {
+        let connection = connection::get_named(Self::connection_name())?;
+        if Self::tenancy() == Tenancy::Database {
+            let tenant = tenant::current().ok_or_else(|| {
+                MongooseError::Tenant(format!("{} requires a tenant scope", Self::name()))
+            })?;
+            return tenant::database(
+                Self::connection_name(),
+                &connection,
+                &Self::tenant_database(&tenant),
+            );
+        }
+        Ok(Self::database_name()
+            .map_or(connection.database, |name| connection.client.database(name)))
+    }
+    async fn collection() -> Result<Collection<Self>, MongooseError> {
+        Ok(Self::database().await?.collection::<Self>(&Self::name()))
     }
     async fn create_view(source: impl ToString, pipeline: Vec<Document>) -> bool {
-        match Self::database()
-            .await
+        let database = match Self::database().await {
+            Ok(database) => database,
+            Err(err) => {
+                tracing::error!("error creating {:?} view: {:?}", Self::name(), err);
+                return false;
+            }
+        };
+        match database
             .create_collection(
                 Self::name(),
                 CreateCollectionOptions::builder()
@@ -52,17 +151,26 @@ where
         }
     }
 
+    // declared indexes, kept in sync by `sync_indexes`
+    // see `#[index]` on `#[derive(Model)]`
+    fn indexes() -> Vec<IndexModel> {
+        Vec::new()
+    }
+
     fn name() -> String {
         use convert_case::{Case, Casing};
+        if let Some(name) = Self::COLLECTION_NAME {
+            return name.to_string();
+        }
         let name = std::any::type_name::<Self>();
         name.split("::").last().map_or_else(
             || name.to_string(),
             |name| {
-                let mut normalized = name.to_case(Case::Snake);
-                if !normalized.ends_with('s') {
-                    normalized.push('s');
+                let normalized = name.to_case(Case::Snake);
+                match Self::NAMING {
+                    Naming::Inflected => inflection::pluralize(&normalized),
+                    Naming::Legacy => inflection::legacy_pluralize(&normalized),
                 }
-                normalized
             },
         )
     }
@@ -85,61 +193,128 @@ where
         )
     }
 
-    fn normalize_updates(updates: &Document) -> Document {
-        let (mut set_updates, mut document_updates) =
-            updates
-                .keys()
-                .fold((Document::new(), Document::new()), |mut acc, key| {
-                    let val = updates.get(key);
-                    if val.is_none() || key == "$set" {
-                        // $set is built internally, so skip it
-                        return acc;
-                    }
-                    if key.starts_with('$') {
-                        // indicates something like $inc / $push / $pull
-                        acc.1.insert(key, val);
-                    } else {
-                        // all other document field updates contained in $set
-                        acc.0.insert(key, val);
-                    }
-                    acc
-                });
-        // update timestamp
-        #[cfg(feature = "timestamps")]
-        set_updates.insert("updated_at", bson::DateTime::now());
-        document_updates.insert("$set", set_updates);
-        // overall document now looks something like:
-        // { $set: { "updated_at": Date, ... }, "$inc": { ... }, "$push": { ... } }
-        document_updates
+    // plain keys and `$set` are merged into a single `$set` carrying `updated_at`
+    // paths written by more than one operator, or nested in one another, are rejected
+    fn normalize_updates(updates: &Document) -> Result<Document, MongooseError> {
+        update::normalize(updates)
+    }
+
+    // lifecycle hooks, an error from a `pre_` hook aborts the operation
+    // `post_` hooks run once the write is committed, so their errors are logged and the call
+    // still succeeds, except in the `_with_session` variants where they abort the transaction
+    // `MongooseError::Hook` is there for errors raised by the hooks themselves
+    async fn pre_save(&mut self) -> Result<(), MongooseError> {
+        Ok(())
+    }
+
+    async fn post_save(&self) -> Result<(), MongooseError> {
+        Ok(())
+    }
+
+    async fn pre_update(
+        _filter: &mut Document,
+        _updates: &mut Document,
+    ) -> Result<(), MongooseError> {
+        Ok(())
+    }
+
+    async fn post_update(_filter: &Document, _updates: &Document) -> Result<(), MongooseError> {
+        Ok(())
+    }
+
+    // run by `update_pipeline` and `bulk_update_pipeline` in place of the update hooks above,
+    // a model guarding its updates in `pre_update` should guard pipelines here too
+    async fn pre_update_pipeline(
+        _filter: &mut Document,
+        _pipeline: &mut Vec<Document>,
+    ) -> Result<(), MongooseError> {
+        Ok(())
+    }
+
+    async fn post_update_pipeline(
+        _filter: &Document,
+        _pipeline: &[Document],
+    ) -> Result<(), MongooseError> {
+        Ok(())
+    }
+
+    async fn pre_delete(_filter: &mut Document) -> Result<(), MongooseError> {
+        Ok(())
+    }
+
+    async fn post_delete(_filter: &Document, _result: &DeleteResult) -> Result<(), MongooseError> {
+        Ok(())
+    }
+
+    // run by `save` and `bulk_insert` after `pre_save`, see `#[validate(...)]` on `#[derive(Model)]`
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        Ok(())
     }
 
     // client api methods
     async fn save(&self) -> Result<Self, MongooseError> {
-        Self::collection()
-            .await
-            .insert_one(self, None)
-            .await
-            .map_err(MongooseError::insert_one)?;
-        Ok(self.clone())
+        let (model, document) = prepare_insert(self).await?;
+        let collection = Self::collection().await?;
+        let saved = if let Some(document) = document {
+            collection
+                .clone_with_type::<Document>()
+                .insert_one(&document, None)
+                .await
+                .map_err(MongooseError::insert_one)?;
+            bson::from_document(document).map_err(MongooseError::insert_one)?
+        } else {
+            collection
+                .insert_one(&model, None)
+                .await
+                .map_err(MongooseError::insert_one)?;
+            model
+        };
+        committed(saved.post_save().await);
+        Ok(saved)
     }
 
     async fn bulk_insert(docs: &[Self]) -> Result<InsertManyResult, MongooseError> {
-        Self::collection()
-            .await
-            .insert_many(docs, None)
-            .await
-            .map_err(MongooseError::bulk_insert)
+        let (models, documents) = prepare_inserts(docs).await?;
+        let collection = Self::collection().await?;
+        let inserted = if let Some(documents) = documents {
+            collection
+                .clone_with_type::<Document>()
+                .insert_many(documents, None)
+                .await
+        } else {
+            collection.insert_many(&models, None).await
+        }
+        .map_err(MongooseError::bulk_insert)?;
+        for model in &models {
+            committed(model.post_save().await);
+        }
+        Ok(inserted)
+    }
+
+    // fluent alternative to passing filter documents, see `Query`
+    fn find() -> Query<Self> {
+        Query::new()
     }
 
     async fn read(filter: Document) -> Result<Self, MongooseError> {
+        Self::read_as::<Self>(filter, None).await
+    }
+
+    // reads into a smaller projection type, only fetching the fields it needs
+    async fn read_as<P: DeserializeOwned + Unpin + Send + Sync>(
+        filter: Document,
+        projection: impl Into<Option<Document>>,
+    ) -> Result<P, MongooseError> {
+        let options = FindOneOptions::builder()
+            .projection(projection.into())
+            .build();
         Self::collection()
-            .await
-            .find_one(filter, None)
+            .await?
+            .clone_with_type::<P>()
+            .find_one(tenant::filter::<Self>(filter)?, options)
             .await
             .map_err(MongooseError::not_found)?
-            .ok_or_else(|| {
-                MongooseError::NotFound("no documents returned matching filter".to_string())
-            })
+            .ok_or_else(|| MongooseError::NotFound("no documents returned matching filter".into()))
     }
 
     async fn read_by_id(id: impl ToString + Send) -> Result<Self, MongooseError> {
@@ -153,31 +328,159 @@ where
     }
 
     async fn list(filter: Document, options: ListOptions) -> Result<Vec<Self>, MongooseError> {
-        let opts = FindOptions::builder()
-            .skip(options.skip)
-            .limit(options.limit)
-            .sort(options.sort)
-            .allow_disk_use(options.allow_disk_use)
-            .projection(None)
-            .build();
-        let mut result_cursor = Self::collection()
+        Self::list_as::<Self>(filter, options).await
+    }
+
+    async fn list_as<P: DeserializeOwned + Unpin + Send + Sync + 'static>(
+        filter: Document,
+        options: ListOptions,
+    ) -> Result<Vec<P>, MongooseError> {
+        Self::stream_as::<P>(filter, options)
+            .await?
+            .try_collect()
             .await
-            .find(filter, opts)
+    }
+
+    // yields documents as the cursor fetches them, `options.batch_size` controls round trips
+    // `None` streams every match, note `ListOptions::default()` stops after 1000 documents
+    async fn stream(
+        filter: Document,
+        options: impl Into<Option<ListOptions>> + Send,
+    ) -> Result<BoxStream<'static, Result<Self, MongooseError>>, MongooseError> {
+        Self::stream_as::<Self>(filter, options).await
+    }
+
+    async fn stream_as<P: DeserializeOwned + Unpin + Send + Sync + 'static>(
+        filter: Document,
+        options: impl Into<Option<ListOptions>> + Send,
+    ) -> Result<BoxStream<'static, Result<P, MongooseError>>, MongooseError> {
+        let options = options.into().unwrap_or(ListOptions {
+            limit: 0,
+            ..Default::default()
+        });
+        let cursor = Self::collection()
+            .await?
+            .clone_with_type::<P>()
+            .find(tenant::filter::<Self>(filter)?, FindOptions::from(options))
             .await
             .map_err(MongooseError::list)?;
-        let mut list_result = vec![];
-        while let Some(cursor) = result_cursor.next().await {
-            list_result.push(cursor.map_err(MongooseError::list)?);
-        }
-        Ok(list_result)
+        Ok(cursor.map_err(MongooseError::list).boxed())
     }
 
-    async fn update(filter: Document, updates: Document) -> Result<Self, MongooseError> {
-        Self::collection()
+    // like `list`, but malformed documents are returned as failures instead of aborting
+    async fn list_lenient(
+        filter: Document,
+        options: ListOptions,
+    ) -> Result<LenientList<Self>, MongooseError> {
+        let mut cursor = Self::collection()
+            .await?
+            .clone_with_type::<Document>()
+            .find(tenant::filter::<Self>(filter)?, FindOptions::from(options))
             .await
+            .map_err(MongooseError::list)?;
+        let mut list = LenientList::default();
+        while let Some(document) = cursor.next().await {
+            list.push(document.map_err(MongooseError::list)?, MongooseError::list);
+        }
+        Ok(list)
+    }
+
+    // items and total count in a single round trip
+    async fn paginate(filter: Document, options: ListOptions) -> Result<Page<Self>, MongooseError> {
+        let pipeline = pagination::facet_pipeline(
+            filter,
+            options.sort,
+            options.projection,
+            options.skip,
+            options.limit,
+        )?;
+        let aggregate_options = AggregateOptions::builder()
+            .allow_disk_use(options.allow_disk_use)
+            .build();
+        let facet = Self::aggregate::<Facet<Self>>(pipeline, aggregate_options)
+            .await?
+            .pop()
+            .ok_or_else(|| MongooseError::Pagination("$facet returned no document".into()))?;
+        let total = facet.total.first().map_or(0, |total| total.count);
+        let seen = options.skip + facet.items.len() as u64;
+        Ok(Page {
+            items: facet.items,
+            total,
+            limit: options.limit,
+            skip: options.skip,
+            has_next: seen < total,
+            has_prev: options.skip > 0,
+        })
+    }
+
+    // cursor based pagination, stable under concurrent inserts and cheap on deep pages
+    async fn keyset_paginate(
+        filter: Document,
+        options: KeysetOptions,
+    ) -> Result<KeysetPage<Self>, MongooseError> {
+        if options.limit <= 0 {
+            return Err(MongooseError::Pagination(
+                "keyset pagination requires a positive limit".into(),
+            ));
+        }
+        let keys = pagination::sort_keys(&options.sort)?;
+        let cursor = options.cursor.as_deref().map(Cursor::decode).transpose()?;
+        let direction = cursor
+            .as_ref()
+            .map_or(Direction::After, |cursor| cursor.direction);
+        let filter = match &cursor {
+            Some(cursor) => doc! { "$and": [filter, pagination::range_filter(&keys, cursor)?] },
+            None => filter,
+        };
+        let limit = usize::try_from(options.limit).unwrap_or_default();
+        let mut items = Self::list(
+            filter,
+            ListOptions {
+                // one extra document tells us whether another page exists
+                limit: options.limit + 1,
+                sort: pagination::sort(&keys, direction),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+        if direction == Direction::Before {
+            items.reverse();
+        }
+        let position = |item: Option<&Self>, direction| {
+            item.map(|item| {
+                let document = bson::to_document(item).map_err(MongooseError::pagination)?;
+                Cursor::at(direction, &keys, &document).encode()
+            })
+            .transpose()
+        };
+        let (has_next, has_prev) = match direction {
+            Direction::After => (has_more, cursor.is_some()),
+            Direction::Before => (true, has_more),
+        };
+        Ok(KeysetPage {
+            next_cursor: if has_next {
+                position(items.last(), Direction::After)?
+            } else {
+                None
+            },
+            prev_cursor: if has_prev {
+                position(items.first(), Direction::Before)?
+            } else {
+                None
+            },
+            items,
+        })
+    }
+
+    async fn update(mut filter: Document, mut updates: Document) -> Result<Self, MongooseError> {
+        Self::pre_update(&mut filter, &mut updates).await?;
+        let updated = Self::collection()
+            .await?
             .find_one_and_update(
-                filter,
-                Self::normalize_updates(&updates),
+                tenant::filter::<Self>(filter.clone())?,
+                tenant::updates::<Self>(Self::normalize_updates(&updates)?)?,
                 FindOneAndUpdateOptions::builder()
                     .return_document(ReturnDocument::After)
                     .build(),
@@ -185,48 +488,110 @@ where
             .await
             .map_err(MongooseError::update)?
             .ok_or_else(|| {
-                MongooseError::NotFound("no documents returned matching filter".to_string())
-            })
+                MongooseError::NotFound("no documents returned matching filter".into())
+            })?;
+        committed(Self::post_update(&filter, &updates).await);
+        Ok(updated)
     }
 
     async fn bulk_update(
-        filter: Document,
-        updates: Document,
+        mut filter: Document,
+        mut updates: Document,
     ) -> Result<UpdateResult, MongooseError> {
-        Self::collection()
-            .await
-            .update_many(filter, Self::normalize_updates(&updates), None)
+        Self::pre_update(&mut filter, &mut updates).await?;
+        let result = Self::collection()
+            .await?
+            .update_many(
+                tenant::filter::<Self>(filter.clone())?,
+                tenant::updates::<Self>(Self::normalize_updates(&updates)?)?,
+                None,
+            )
             .await
-            .map_err(MongooseError::bulk_update)
+            .map_err(MongooseError::bulk_update)?;
+        committed(Self::post_update(&filter, &updates).await);
+        Ok(result)
     }
 
-    async fn delete(filter: Document) -> Result<DeleteResult, MongooseError> {
-        Self::collection()
+    // aggregation pipeline updates, stages can reference other fields of the document
+    // `pre_update_pipeline` and `post_update_pipeline` run instead of the operator update hooks
+    async fn update_pipeline(
+        mut filter: Document,
+        mut pipeline: Vec<Document>,
+    ) -> Result<Self, MongooseError> {
+        Self::pre_update_pipeline(&mut filter, &mut pipeline).await?;
+        let updated = Self::collection()
+            .await?
+            .find_one_and_update(
+                tenant::filter::<Self>(filter.clone())?,
+                update::pipeline(tenant::update_pipeline::<Self>(pipeline.clone())?),
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
             .await
-            .delete_one(filter, None)
+            .map_err(MongooseError::update)?
+            .ok_or_else(|| {
+                MongooseError::NotFound("no documents returned matching filter".into())
+            })?;
+        committed(Self::post_update_pipeline(&filter, &pipeline).await);
+        Ok(updated)
+    }
+
+    async fn bulk_update_pipeline(
+        mut filter: Document,
+        mut pipeline: Vec<Document>,
+    ) -> Result<UpdateResult, MongooseError> {
+        Self::pre_update_pipeline(&mut filter, &mut pipeline).await?;
+        let result = Self::collection()
+            .await?
+            .update_many(
+                tenant::filter::<Self>(filter.clone())?,
+                update::pipeline(tenant::update_pipeline::<Self>(pipeline.clone())?),
+                None,
+            )
             .await
-            .map_err(MongooseError::delete)
+            .map_err(MongooseError::bulk_update)?;
+        committed(Self::post_update_pipeline(&filter, &pipeline).await);
+        Ok(result)
     }
 
-    async fn bulk_delete(filter: Document) -> Result<DeleteResult, MongooseError> {
-        Self::collection()
+    async fn delete(mut filter: Document) -> Result<DeleteResult, MongooseError> {
+        Self::pre_delete(&mut filter).await?;
+        let result = Self::collection()
+            .await?
+            .delete_one(tenant::filter::<Self>(filter.clone())?, None)
             .await
-            .delete_many(filter, None)
+            .map_err(MongooseError::delete)?;
+        committed(Self::post_delete(&filter, &result).await);
+        Ok(result)
+    }
+
+    async fn bulk_delete(mut filter: Document) -> Result<DeleteResult, MongooseError> {
+        Self::pre_delete(&mut filter).await?;
+        let result = Self::collection()
+            .await?
+            .delete_many(tenant::filter::<Self>(filter.clone())?, None)
             .await
-            .map_err(MongooseError::bulk_delete)
+            .map_err(MongooseError::bulk_delete)?;
+        committed(Self::post_delete(&filter, &result).await);
+        Ok(result)
     }
 
     async fn count(filter: Option<Document>) -> Result<u64, MongooseError> {
         Self::collection()
-            .await
-            .count_documents(filter, None)
+            .await?
+            .count_documents(tenant::filter::<Self>(filter.unwrap_or_default())?, None)
             .await
             .map_err(MongooseError::count)
     }
 
     async fn estimated_collection_count() -> Result<u64, MongooseError> {
+        if matches!(Self::tenancy(), Tenancy::Field(_)) {
+            // collection metadata would count every tenant's rows
+            return Self::count(None).await;
+        }
         Self::collection()
-            .await
+            .await?
             .estimated_document_count(None)
             .await
             .map_err(MongooseError::count)
@@ -237,25 +602,301 @@ where
         options: impl Into<Option<AggregateOptions>>,
     ) -> Result<Vec<T>, MongooseError> {
         let mut result_cursor = Self::collection()
-            .await
-            .aggregate(pipeline, options)
+            .await?
+            .aggregate(tenant::pipeline::<Self>(pipeline)?, options)
             .await
             .map_err(MongooseError::aggregate)?;
         let mut aggregate_docs = vec![];
         while let Some(cursor) = result_cursor.next().await {
             let document = cursor.map_err(MongooseError::aggregate)?;
-            let data = bson::from_document::<T>(document)
-                .map_err(|err| MongooseError::Aggregate(err.to_string()))?;
+            let data = bson::from_document::<T>(document).map_err(MongooseError::deserialize)?;
             aggregate_docs.push(data);
         }
         Ok(aggregate_docs)
     }
 
-    async fn create_indexes(options: &[IndexModel]) -> Result<CreateIndexesResult, MongooseError> {
+    async fn aggregate_lenient<T: DeserializeOwned + Send>(
+        pipeline: Vec<Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<LenientList<T>, MongooseError> {
+        let mut cursor = Self::collection()
+            .await?
+            .aggregate(tenant::pipeline::<Self>(pipeline)?, options)
+            .await
+            .map_err(MongooseError::aggregate)?;
+        let mut list = LenientList::default();
+        while let Some(document) = cursor.next().await {
+            list.push(
+                document.map_err(MongooseError::aggregate)?,
+                MongooseError::aggregate,
+            );
+        }
+        Ok(list)
+    }
+
+    // deserializes raw documents one by one and groups the ones that fail
+    // samples `sample_size` random documents, or scans the whole collection when `None`
+    async fn check_schema(sample_size: Option<u64>) -> Result<SchemaReport, MongooseError> {
+        let pipeline = sample_size
+            .map(|size| {
+                vec![doc! { "$sample": { "size": i64::try_from(size).unwrap_or(i64::MAX) } }]
+            })
+            .unwrap_or_default();
+        let mut cursor = Self::collection()
+            .await?
+            .clone_with_type::<Document>()
+            .aggregate(tenant::pipeline::<Self>(pipeline)?, None)
+            .await
+            .map_err(MongooseError::schema)?;
+        let mut report = SchemaReport::default();
+        while let Some(document) = cursor.next().await {
+            report.check::<Self>(document.map_err(MongooseError::schema)?);
+        }
+        Ok(report.finish())
+    }
+
+    // field scoped models only see events of the current tenant, and get updates with their full
+    // document looked up; deletes need pre-images, see `tenant::change_stream`
+    async fn watch(
+        pipeline: Vec<Document>,
+        options: impl Into<Option<ChangeStreamOptions>>,
+    ) -> Result<ChangeStream<Self>, MongooseError> {
+        let (pipeline, options) = tenant::change_stream::<Self>(pipeline, options.into())?;
+        let stream = Self::collection()
+            .await?
+            .watch(pipeline, options)
+            .await
+            .map_err(MongooseError::watch)?;
+        Ok(ChangeStream::new(stream))
+    }
+
+    // session api methods, for use inside `mongoose::transaction`
+    async fn save_with_session(&self, session: &mut ClientSession) -> Result<Self, MongooseError> {
+        let (model, document) = prepare_insert(self).await?;
+        let collection = Self::collection().await?;
+        let saved = if let Some(document) = document {
+            collection
+                .clone_with_type::<Document>()
+                .insert_one_with_session(&document, None, session)
+                .await
+                .map_err(|err| MongooseError::in_session(err, MongooseError::insert_one))?;
+            bson::from_document(document).map_err(MongooseError::insert_one)?
+        } else {
+            collection
+                .insert_one_with_session(&model, None, session)
+                .await
+                .map_err(|err| MongooseError::in_session(err, MongooseError::insert_one))?;
+            model
+        };
+        saved.post_save().await?;
+        Ok(saved)
+    }
+
+    async fn bulk_insert_with_session(
+        docs: &[Self],
+        session: &mut ClientSession,
+    ) -> Result<InsertManyResult, MongooseError> {
+        let (models, documents) = prepare_inserts(docs).await?;
+        let collection = Self::collection().await?;
+        let inserted = if let Some(documents) = documents {
+            collection
+                .clone_with_type::<Document>()
+                .insert_many_with_session(documents, None, session)
+                .await
+        } else {
+            collection
+                .insert_many_with_session(&models, None, session)
+                .await
+        }
+        .map_err(|err| MongooseError::in_session(err, MongooseError::bulk_insert))?;
+        for model in &models {
+            model.post_save().await?;
+        }
+        Ok(inserted)
+    }
+
+    async fn read_with_session(
+        filter: Document,
+        session: &mut ClientSession,
+    ) -> Result<Self, MongooseError> {
         Self::collection()
+            .await?
+            .find_one_with_session(tenant::filter::<Self>(filter)?, None, session)
             .await
+            .map_err(|err| MongooseError::in_session(err, MongooseError::not_found))?
+            .ok_or_else(|| MongooseError::NotFound("no documents returned matching filter".into()))
+    }
+
+    async fn list_with_session(
+        filter: Document,
+        options: ListOptions,
+        session: &mut ClientSession,
+    ) -> Result<Vec<Self>, MongooseError> {
+        let mut result_cursor = Self::collection()
+            .await?
+            .find_with_session(
+                tenant::filter::<Self>(filter)?,
+                FindOptions::from(options),
+                session,
+            )
+            .await
+            .map_err(|err| MongooseError::in_session(err, MongooseError::list))?;
+        let mut list_result = vec![];
+        while let Some(cursor) = result_cursor.next(session).await {
+            list_result
+                .push(cursor.map_err(|err| MongooseError::in_session(err, MongooseError::list))?);
+        }
+        Ok(list_result)
+    }
+
+    async fn update_with_session(
+        mut filter: Document,
+        mut updates: Document,
+        session: &mut ClientSession,
+    ) -> Result<Self, MongooseError> {
+        Self::pre_update(&mut filter, &mut updates).await?;
+        let updated = Self::collection()
+            .await?
+            .find_one_and_update_with_session(
+                tenant::filter::<Self>(filter.clone())?,
+                tenant::updates::<Self>(Self::normalize_updates(&updates)?)?,
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+                session,
+            )
+            .await
+            .map_err(|err| MongooseError::in_session(err, MongooseError::update))?
+            .ok_or_else(|| {
+                MongooseError::NotFound("no documents returned matching filter".into())
+            })?;
+        Self::post_update(&filter, &updates).await?;
+        Ok(updated)
+    }
+
+    async fn delete_with_session(
+        mut filter: Document,
+        session: &mut ClientSession,
+    ) -> Result<DeleteResult, MongooseError> {
+        Self::pre_delete(&mut filter).await?;
+        let result = Self::collection()
+            .await?
+            .delete_one_with_session(tenant::filter::<Self>(filter.clone())?, None, session)
+            .await
+            .map_err(|err| MongooseError::in_session(err, MongooseError::delete))?;
+        Self::post_delete(&filter, &result).await?;
+        Ok(result)
+    }
+
+    async fn aggregate_with_session<T: DeserializeOwned + Send>(
+        pipeline: Vec<Document>,
+        options: impl Into<Option<AggregateOptions>>,
+        session: &mut ClientSession,
+    ) -> Result<Vec<T>, MongooseError> {
+        let mut result_cursor = Self::collection()
+            .await?
+            .aggregate_with_session(tenant::pipeline::<Self>(pipeline)?, options, session)
+            .await
+            .map_err(|err| MongooseError::in_session(err, MongooseError::aggregate))?;
+        let mut aggregate_docs = vec![];
+        while let Some(cursor) = result_cursor.next(session).await {
+            let document =
+                cursor.map_err(|err| MongooseError::in_session(err, MongooseError::aggregate))?;
+            let data = bson::from_document::<T>(document).map_err(MongooseError::deserialize)?;
+            aggregate_docs.push(data);
+        }
+        Ok(aggregate_docs)
+    }
+
+    // batch size is set through `AggregateOptions::batch_size`
+    async fn aggregate_stream<T: DeserializeOwned + Send + 'static>(
+        pipeline: Vec<Document>,
+        options: impl Into<Option<AggregateOptions>>,
+    ) -> Result<BoxStream<'static, Result<T, MongooseError>>, MongooseError> {
+        let cursor = Self::collection()
+            .await?
+            .aggregate(tenant::pipeline::<Self>(pipeline)?, options)
+            .await
+            .map_err(MongooseError::aggregate)?;
+        Ok(cursor
+            .map(|document| {
+                bson::from_document::<T>(document.map_err(MongooseError::aggregate)?)
+                    .map_err(MongooseError::deserialize)
+            })
+            .boxed())
+    }
+
+    async fn create_indexes(options: &[IndexModel]) -> Result<CreateIndexesResult, MongooseError> {
+        Self::collection()
+            .await?
             .create_indexes(options.to_vec(), None)
             .await
             .map_err(MongooseError::create_index)
     }
+
+    // creates missing indexes, recreates changed ones and drops undeclared ones
+    async fn sync_indexes(options: SyncIndexOptions) -> Result<IndexSyncReport, MongooseError> {
+        let collection = Self::collection().await?;
+        let existing = match collection.list_indexes(None).await {
+            Ok(cursor) => cursor
+                .try_collect::<Vec<_>>()
+                .await
+                .map_err(MongooseError::sync_indexes)?,
+            Err(err) if is_namespace_not_found(&err) => {
+                vec![]
+            }
+            Err(err) => return Err(MongooseError::sync_indexes(err)),
+        };
+        let plan = indexes::plan(&Self::indexes(), &existing, options.dry_run);
+        if options.dry_run {
+            return Ok(plan.report);
+        }
+        for name in &plan.drop {
+            collection
+                .drop_index(name, None)
+                .await
+                .map_err(MongooseError::sync_indexes)?;
+        }
+        if !plan.create.is_empty() {
+            collection
+                .create_indexes(plan.create, None)
+                .await
+                .map_err(MongooseError::create_index)?;
+        }
+        Ok(plan.report)
+    }
+
+    // installs `Self::bson_schema()` as the collection's `$jsonSchema` validator
+    // creating the collection when it does not exist yet
+    async fn apply_schema_validator(
+        level: ValidationLevel,
+        action: ValidationAction,
+    ) -> Result<(), MongooseError>
+    where
+        Self: BsonSchema,
+    {
+        let database = Self::database().await?;
+        let validator = doc! { "$jsonSchema": Self::bson_schema() };
+        let command = doc! {
+            "collMod": Self::name(),
+            "validator": &validator,
+            "validationLevel": bson::to_bson(&level).map_err(MongooseError::schema)?,
+            "validationAction": bson::to_bson(&action).map_err(MongooseError::schema)?,
+        };
+        match database.run_command(command, None).await {
+            Ok(_) => Ok(()),
+            Err(err) if is_namespace_not_found(&err) => {
+                let options = CreateCollectionOptions::builder()
+                    .validator(validator)
+                    .validation_level(level)
+                    .validation_action(action)
+                    .build();
+                database
+                    .create_collection(Self::name(), options)
+                    .await
+                    .map_err(MongooseError::schema)
+            }
+            Err(err) => Err(MongooseError::schema(err)),
+        }
+    }
 }
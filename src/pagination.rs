@@ -0,0 +1,212 @@
+use crate::types::MongooseError;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bson::{doc, Bson, Document};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeysetOptions {
+    pub limit: i64,
+    // `_id` is appended as a tie breaker when missing
+    pub sort: Document,
+    // `next_cursor` or `prev_cursor` from a previous page
+    pub cursor: Option<String>,
+}
+
+impl Default for KeysetOptions {
+    fn default() -> Self {
+        Self {
+            limit: 100,
+            sort: Document::default(),
+            cursor: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeysetPage<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub limit: i64,
+    pub skip: u64,
+    pub has_next: bool,
+    pub has_prev: bool,
+}
+
+// shape of the `$facet` stage used by `Model::paginate`
+#[derive(Deserialize, Debug)]
+pub(crate) struct Facet<T> {
+    pub items: Vec<T>,
+    pub total: Vec<FacetCount>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct FacetCount {
+    pub count: u64,
+}
+
+// a `limit` of 0 returns every match after `skip`, like `ListOptions`
+pub(crate) fn facet_pipeline(
+    filter: Document,
+    sort: Document,
+    projection: Option<Document>,
+    skip: u64,
+    limit: i64,
+) -> Result<Vec<Document>, MongooseError> {
+    if limit < 0 {
+        return Err(MongooseError::Pagination(
+            format!("limit must not be negative, got {limit}").into(),
+        ));
+    }
+    let skip = i64::try_from(skip).map_err(MongooseError::pagination)?;
+    let mut items = vec![];
+    if !sort.is_empty() {
+        items.push(doc! { "$sort": sort });
+    }
+    items.push(doc! { "$skip": skip });
+    if limit > 0 {
+        items.push(doc! { "$limit": limit });
+    }
+    if let Some(projection) = projection {
+        items.push(doc! { "$project": projection });
+    }
+    Ok(vec![
+        doc! { "$match": filter },
+        doc! { "$facet": {
+            "items": items,
+            "total": [{ "$count": "count" }],
+        } },
+    ])
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    #[serde(rename = "a")]
+    After,
+    #[serde(rename = "b")]
+    Before,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Cursor {
+    #[serde(rename = "d")]
+    pub direction: Direction,
+    #[serde(rename = "v")]
+    pub values: Vec<Bson>,
+}
+
+impl Cursor {
+    // captures the sort key values of `document`, missing paths become null
+    pub fn at(direction: Direction, keys: &[(String, i32)], document: &Document) -> Self {
+        let values = keys
+            .iter()
+            .map(|(key, _)| lookup(document, key).cloned().unwrap_or(Bson::Null))
+            .collect();
+        Self { direction, values }
+    }
+
+    pub fn encode(&self) -> Result<String, MongooseError> {
+        let bytes = bson::to_vec(self).map_err(MongooseError::pagination)?;
+        Ok(URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, MongooseError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(MongooseError::pagination)?;
+        bson::from_slice(&bytes).map_err(MongooseError::pagination)
+    }
+}
+
+fn lookup<'a>(document: &'a Document, path: &str) -> Option<&'a Bson> {
+    let mut parts = path.split('.');
+    let mut value = document.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_document()?.get(part)?;
+    }
+    Some(value)
+}
+
+// validates the sort spec, returning (path, 1 | -1) pairs ending in `_id`
+pub(crate) fn sort_keys(sort: &Document) -> Result<Vec<(String, i32)>, MongooseError> {
+    let mut keys = sort
+        .iter()
+        .map(|(key, value)| {
+            let direction = match value {
+                Bson::Int32(direction) => i64::from(*direction),
+                Bson::Int64(direction) => *direction,
+                #[allow(clippy::cast_possible_truncation)]
+                Bson::Double(direction) => *direction as i64,
+                _ => 0,
+            };
+            match direction {
+                1 => Ok((key.clone(), 1)),
+                -1 => Ok((key.clone(), -1)),
+                _ => Err(MongooseError::Pagination(
+                    format!("sort direction for {key:?} must be 1 or -1").into(),
+                )),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if !keys.iter().any(|(key, _)| key == "_id") {
+        let direction = keys.last().map_or(1, |(_, direction)| *direction);
+        keys.push(("_id".to_string(), direction));
+    }
+    Ok(keys)
+}
+
+// the sort to query with, flipped when walking backwards
+pub(crate) fn sort(keys: &[(String, i32)], direction: Direction) -> Document {
+    keys.iter()
+        .map(|(key, order)| {
+            let order = if direction == Direction::Before {
+                -order
+            } else {
+                *order
+            };
+            (key.clone(), Bson::Int32(order))
+        })
+        .collect()
+}
+
+// matches everything strictly after (or before) the cursor position
+// for keys a, b, c: { $or: [{ a > x }, { a = x, b > y }, { a = x, b = y, c > z }] }
+pub(crate) fn range_filter(
+    keys: &[(String, i32)],
+    cursor: &Cursor,
+) -> Result<Document, MongooseError> {
+    if cursor.values.len() != keys.len() {
+        return Err(MongooseError::Pagination(
+            "cursor was created for a different sort".into(),
+        ));
+    }
+    let branches = keys
+        .iter()
+        .zip(&cursor.values)
+        .enumerate()
+        .filter_map(|(index, ((key, order), value))| {
+            let ascending = (*order == 1) == (cursor.direction == Direction::After);
+            // null and missing values sort before everything else
+            let bound = match (ascending, value) {
+                (true, Bson::Null) => doc! { key: { "$ne": null } },
+                (false, Bson::Null) => return None,
+                (true, value) => doc! { key: { "$gt": value } },
+                (false, value) => doc! { "$or": [{ key: { "$lt": value } }, { key: null }] },
+            };
+            let mut branch = keys[..index]
+                .iter()
+                .zip(&cursor.values)
+                .map(|((key, _), value)| (key.clone(), value.clone()))
+                .collect::<Document>();
+            branch.extend(bound);
+            Some(branch)
+        })
+        .collect::<Vec<_>>();
+    Ok(doc! { "$or": branches })
+}
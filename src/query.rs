@@ -0,0 +1,383 @@
+use crate::{
+    types::{ListOptions, MongooseError},
+    Model,
+};
+use bson::{doc, Bson, Document};
+use futures::stream::BoxStream;
+use mongodb::results::DeleteResult;
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+// anything conditions can be added to, `Filter` and `Query`
+pub trait Conditions: Sized {
+    #[must_use]
+    fn condition(self, condition: Document) -> Self;
+    #[must_use]
+    fn invalid(self, error: String) -> Self;
+}
+
+// a filter document built clause by clause
+//
+// Filter::new().field("age").gte(18).field("address.state").eq("CA")
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    clauses: Vec<Document>,
+    // first value that failed to serialize, reported by `build`
+    error: Option<String>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(self, path: impl AsRef<str>) -> Field<Self> {
+        Field::new(self, path)
+    }
+
+    // adds a hand written clause
+    #[must_use]
+    pub fn raw(self, clause: Document) -> Self {
+        self.condition(clause)
+    }
+
+    #[must_use]
+    pub fn and(self, filters: impl IntoIterator<Item = Self>) -> Self {
+        self.logical("$and", filters)
+    }
+
+    #[must_use]
+    pub fn or(self, filters: impl IntoIterator<Item = Self>) -> Self {
+        self.logical("$or", filters)
+    }
+
+    #[must_use]
+    pub fn nor(self, filters: impl IntoIterator<Item = Self>) -> Self {
+        self.logical("$nor", filters)
+    }
+
+    fn logical(self, operator: &str, filters: impl IntoIterator<Item = Self>) -> Self {
+        let filters = filters
+            .into_iter()
+            .map(Self::build)
+            .collect::<Result<Vec<_>, _>>();
+        match filters {
+            Ok(filters) => self.condition(doc! { operator: filters }),
+            Err(err) => self.invalid(err.to_string()),
+        }
+    }
+
+    // clauses are merged into one document, or combined with `$and` when keys repeat
+    pub fn build(self) -> Result<Document, MongooseError> {
+        if let Some(error) = self.error {
+            return Err(MongooseError::InvalidQuery(error));
+        }
+        let mut merged = Document::new();
+        for clause in &self.clauses {
+            for (key, value) in clause {
+                if merged.contains_key(key) {
+                    return Ok(doc! { "$and": self.clauses });
+                }
+                merged.insert(key, value.clone());
+            }
+        }
+        Ok(merged)
+    }
+}
+
+impl Conditions for Filter {
+    fn condition(mut self, condition: Document) -> Self {
+        self.clauses.push(condition);
+        self
+    }
+
+    fn invalid(mut self, error: String) -> Self {
+        self.error.get_or_insert(error);
+        self
+    }
+}
+
+// a pending condition on one path, finished by an operator
+#[must_use = "a field does nothing until an operator like `eq` or `gt` is applied"]
+pub struct Field<Q> {
+    conditions: Q,
+    path: String,
+    negate: bool,
+}
+
+impl<Q: Conditions> Field<Q> {
+    fn new(conditions: Q, path: impl AsRef<str>) -> Self {
+        Self {
+            conditions,
+            path: path.as_ref().to_string(),
+            negate: false,
+        }
+    }
+
+    // negates the next operator with `$not`
+    pub const fn not(mut self) -> Self {
+        self.negate = !self.negate;
+        self
+    }
+
+    fn operator(self, operator: &str, value: Bson) -> Q {
+        let mut expression = doc! { operator: value };
+        if self.negate {
+            expression = doc! { "$not": expression };
+        }
+        self.conditions.condition(doc! { self.path: expression })
+    }
+
+    fn value(self, operator: &str, value: impl Serialize) -> Q {
+        match bson::to_bson(&value) {
+            Ok(value) => self.operator(operator, value),
+            Err(err) => {
+                let error = format!("{}: {err}", self.path);
+                self.conditions.invalid(error)
+            }
+        }
+    }
+
+    fn values<T: Serialize>(self, operator: &str, values: impl IntoIterator<Item = T>) -> Q {
+        self.value(operator, values.into_iter().collect::<Vec<_>>())
+    }
+
+    pub fn eq(self, value: impl Serialize) -> Q {
+        self.value("$eq", value)
+    }
+
+    pub fn ne(self, value: impl Serialize) -> Q {
+        self.value("$ne", value)
+    }
+
+    pub fn gt(self, value: impl Serialize) -> Q {
+        self.value("$gt", value)
+    }
+
+    pub fn gte(self, value: impl Serialize) -> Q {
+        self.value("$gte", value)
+    }
+
+    pub fn lt(self, value: impl Serialize) -> Q {
+        self.value("$lt", value)
+    }
+
+    pub fn lte(self, value: impl Serialize) -> Q {
+        self.value("$lte", value)
+    }
+
+    // `$in`
+    pub fn is_in<T: Serialize>(self, values: impl IntoIterator<Item = T>) -> Q {
+        self.values("$in", values)
+    }
+
+    // `$nin`
+    pub fn not_in<T: Serialize>(self, values: impl IntoIterator<Item = T>) -> Q {
+        self.values("$nin", values)
+    }
+
+    pub fn exists(self, exists: bool) -> Q {
+        self.operator("$exists", Bson::Boolean(exists))
+    }
+
+    // `options` are the usual regex flags, e.g. "i" for case insensitive
+    pub fn regex(self, pattern: impl AsRef<str>, options: impl AsRef<str>) -> Q {
+        // the server expects flags in alphabetical order
+        let mut options = options.as_ref().chars().collect::<Vec<_>>();
+        options.sort_unstable();
+        let regex = bson::Regex {
+            pattern: pattern.as_ref().to_string(),
+            options: options.into_iter().collect(),
+        };
+        self.operator("$regex", Bson::RegularExpression(regex))
+    }
+
+    // array elements matching every condition of `filter`
+    // use `Filter::raw(doc! { "$gte": 80 })` for arrays of scalars
+    pub fn elem_match(self, filter: Filter) -> Q {
+        match filter.build() {
+            Ok(filter) => self.operator("$elemMatch", Bson::Document(filter)),
+            Err(err) => self.conditions.invalid(err.to_string()),
+        }
+    }
+
+    pub fn all<T: Serialize>(self, values: impl IntoIterator<Item = T>) -> Q {
+        self.values("$all", values)
+    }
+
+    pub fn size(self, size: i64) -> Q {
+        self.operator("$size", Bson::Int64(size))
+    }
+}
+
+// a filter plus sort, projection and paging, executed against `M`
+//
+// User::find()
+//     .field("age").gt(18)
+//     .field("address.state").eq("CA")
+//     .sort_desc("created_at")
+//     .limit(10)
+//     .list()
+//     .await?
+pub struct Query<M> {
+    filter: Filter,
+    options: ListOptions,
+    // whether `limit` was called, streams are unlimited otherwise
+    limited: bool,
+    model: PhantomData<M>,
+}
+
+impl<M: Model> Default for Query<M> {
+    fn default() -> Self {
+        Self {
+            filter: Filter::default(),
+            options: ListOptions::default(),
+            limited: false,
+            model: PhantomData,
+        }
+    }
+}
+
+impl<M: Model> Query<M> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(self, path: impl AsRef<str>) -> Field<Self> {
+        Field::new(self, path)
+    }
+
+    #[must_use]
+    pub fn raw(self, clause: Document) -> Self {
+        self.condition(clause)
+    }
+
+    #[must_use]
+    pub fn and(mut self, filters: impl IntoIterator<Item = Filter>) -> Self {
+        self.filter = self.filter.and(filters);
+        self
+    }
+
+    #[must_use]
+    pub fn or(mut self, filters: impl IntoIterator<Item = Filter>) -> Self {
+        self.filter = self.filter.or(filters);
+        self
+    }
+
+    #[must_use]
+    pub fn nor(mut self, filters: impl IntoIterator<Item = Filter>) -> Self {
+        self.filter = self.filter.nor(filters);
+        self
+    }
+
+    #[must_use]
+    pub fn sort_asc(mut self, path: impl AsRef<str>) -> Self {
+        self.options.sort.insert(path.as_ref(), 1);
+        self
+    }
+
+    #[must_use]
+    pub fn sort_desc(mut self, path: impl AsRef<str>) -> Self {
+        self.options.sort.insert(path.as_ref(), -1);
+        self
+    }
+
+    #[must_use]
+    pub const fn limit(mut self, limit: i64) -> Self {
+        self.options.limit = limit;
+        self.limited = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn skip(mut self, skip: u64) -> Self {
+        self.options.skip = skip;
+        self
+    }
+
+    // only fetch these fields, see `list_as` for reading into a smaller type
+    #[must_use]
+    pub fn select<P: AsRef<str>>(self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.project(paths, 1)
+    }
+
+    #[must_use]
+    pub fn exclude<P: AsRef<str>>(self, paths: impl IntoIterator<Item = P>) -> Self {
+        self.project(paths, 0)
+    }
+
+    fn project<P: AsRef<str>>(mut self, paths: impl IntoIterator<Item = P>, value: i32) -> Self {
+        let projection = self.options.projection.get_or_insert_with(Document::new);
+        for path in paths {
+            projection.insert(path.as_ref(), value);
+        }
+        self
+    }
+
+    // the compiled filter and list options
+    pub fn build(self) -> Result<(Document, ListOptions), MongooseError> {
+        Ok((self.filter.build()?, self.options))
+    }
+
+    pub async fn list(self) -> Result<Vec<M>, MongooseError> {
+        let (filter, options) = self.build()?;
+        M::list(filter, options).await
+    }
+
+    pub async fn list_as<P: DeserializeOwned + Unpin + Send + Sync + 'static>(
+        self,
+    ) -> Result<Vec<P>, MongooseError> {
+        let (filter, options) = self.build()?;
+        M::list_as::<P>(filter, options).await
+    }
+
+    // the first match, honoring sort and skip
+    pub async fn read(self) -> Result<M, MongooseError> {
+        self.limit(1)
+            .list()
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| MongooseError::NotFound("no documents returned matching filter".into()))
+    }
+
+    pub async fn count(self) -> Result<u64, MongooseError> {
+        let (filter, _) = self.build()?;
+        M::count(Some(filter)).await
+    }
+
+    // every match unless `limit` was set
+    pub async fn stream(
+        self,
+    ) -> Result<BoxStream<'static, Result<M, MongooseError>>, MongooseError> {
+        let limited = self.limited;
+        let (filter, mut options) = self.build()?;
+        if !limited {
+            options.limit = 0;
+        }
+        M::stream(filter, options).await
+    }
+
+    // deletes the first match
+    pub async fn delete(self) -> Result<DeleteResult, MongooseError> {
+        let (filter, _) = self.build()?;
+        M::delete(filter).await
+    }
+
+    pub async fn delete_many(self) -> Result<DeleteResult, MongooseError> {
+        let (filter, _) = self.build()?;
+        M::bulk_delete(filter).await
+    }
+}
+
+impl<M: Model> Conditions for Query<M> {
+    fn condition(mut self, condition: Document) -> Self {
+        self.filter = self.filter.condition(condition);
+        self
+    }
+
+    fn invalid(mut self, error: String) -> Self {
+        self.filter = self.filter.invalid(error);
+        self
+    }
+}
@@ -0,0 +1,179 @@
+use bson::{doc, Bson, Document};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+// the `$jsonSchema` of a type as it is stored by the bson serializer
+// see `#[derive(BsonSchema)]` for structs
+pub trait BsonSchema {
+    fn bson_schema() -> Document;
+
+    // whether the field must be present, `false` for `Option`
+    fn required() -> bool {
+        true
+    }
+}
+
+fn bson_type(bson_type: impl Into<Bson>) -> Document {
+    doc! { "bsonType": bson_type.into() }
+}
+
+macro_rules! bson_schema {
+    ($bson_type:expr => $($ty:ty),+) => {
+        $(
+            impl BsonSchema for $ty {
+                fn bson_schema() -> Document {
+                    bson_type($bson_type)
+                }
+            }
+        )+
+    };
+}
+
+bson_schema!("string" => String, str, char);
+bson_schema!("bool" => bool);
+// integers are accepted as either width, `doc! { "age": 1 }` updates store an `int`
+// floats accept them too, `doc! { "score": 1 }` would otherwise fail a strict validator
+bson_schema!(vec!["double", "int", "long"] => f32, f64);
+bson_schema!(vec!["int", "long"] => i8, i16, i32, i64, u8, u16, u32, u64, isize, usize);
+bson_schema!("date" => bson::DateTime);
+// as stored through `TimestampSerializer`
+#[cfg(feature = "timestamps")]
+bson_schema!("date" => chrono::DateTime<chrono::Utc>);
+bson_schema!("objectId" => bson::oid::ObjectId);
+bson_schema!("binData" => bson::Binary, bson::Uuid);
+bson_schema!("timestamp" => bson::Timestamp);
+bson_schema!("decimal" => bson::Decimal128);
+bson_schema!("object" => Document);
+
+impl BsonSchema for Bson {
+    // any type
+    fn bson_schema() -> Document {
+        Document::new()
+    }
+}
+
+impl<T: BsonSchema + ?Sized> BsonSchema for &T {
+    fn bson_schema() -> Document {
+        T::bson_schema()
+    }
+}
+
+impl<T: BsonSchema + ?Sized> BsonSchema for Box<T> {
+    fn bson_schema() -> Document {
+        T::bson_schema()
+    }
+}
+
+impl<T: BsonSchema> BsonSchema for Option<T> {
+    fn bson_schema() -> Document {
+        let mut schema = T::bson_schema();
+        let nullable = match schema.get("bsonType") {
+            Some(Bson::String(bson_type)) => {
+                Bson::Array(vec![bson_type.as_str().into(), "null".into()])
+            }
+            Some(Bson::Array(types)) => Bson::Array(
+                types
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once("null".into()))
+                    .collect(),
+            ),
+            _ => return schema,
+        };
+        schema.insert("bsonType", nullable);
+        schema
+    }
+
+    fn required() -> bool {
+        false
+    }
+}
+
+impl<T: BsonSchema> BsonSchema for Vec<T> {
+    fn bson_schema() -> Document {
+        doc! { "bsonType": "array", "items": T::bson_schema() }
+    }
+}
+
+impl<T: BsonSchema> BsonSchema for [T] {
+    fn bson_schema() -> Document {
+        Vec::<T>::bson_schema()
+    }
+}
+
+impl<K, V: BsonSchema, S> BsonSchema for HashMap<K, V, S> {
+    fn bson_schema() -> Document {
+        doc! { "bsonType": "object", "additionalProperties": V::bson_schema() }
+    }
+}
+
+impl<K, V: BsonSchema> BsonSchema for BTreeMap<K, V> {
+    fn bson_schema() -> Document {
+        HashMap::<K, V>::bson_schema()
+    }
+}
+
+#[cfg(feature = "derive")]
+pub use mongoose_derive::BsonSchema;
+
+// example ids kept per failure group
+const MAX_EXAMPLES: usize = 5;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SchemaFailure {
+    // where deserializing stopped, `.` for the document root
+    pub path: String,
+    pub message: String,
+    pub count: u64,
+    pub examples: Vec<Bson>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SchemaReport {
+    pub scanned: u64,
+    pub failed: u64,
+    // most frequent first
+    pub failures: Vec<SchemaFailure>,
+}
+
+impl SchemaReport {
+    pub const fn is_clean(&self) -> bool {
+        self.failed == 0
+    }
+
+    // tries to deserialize `document` as `T`, grouping the failure by path and message
+    pub(crate) fn check<T: DeserializeOwned>(&mut self, document: Document) {
+        self.scanned += 1;
+        let id = document.get("_id").cloned().unwrap_or(Bson::Null);
+        let deserializer = bson::Deserializer::new(Bson::Document(document));
+        let Err(err) = serde_path_to_error::deserialize::<_, T>(deserializer) else {
+            return;
+        };
+        self.failed += 1;
+        let (path, message) = (err.path().to_string(), err.inner().to_string());
+        match self
+            .failures
+            .iter_mut()
+            .find(|failure| failure.path == path && failure.message == message)
+        {
+            Some(failure) => {
+                failure.count += 1;
+                if failure.examples.len() < MAX_EXAMPLES {
+                    failure.examples.push(id);
+                }
+            }
+            None => self.failures.push(SchemaFailure {
+                path,
+                message,
+                count: 1,
+                examples: vec![id],
+            }),
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Self {
+        self.failures
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+        self
+    }
+}
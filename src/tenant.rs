@@ -0,0 +1,258 @@
+use crate::{connection::Connection, types::MongooseError, update, Model};
+use bson::{doc, Bson, Document};
+use lazy_static::lazy_static;
+use mongodb::{
+    options::{ChangeStreamOptions, FullDocumentBeforeChangeType, FullDocumentType},
+    Database,
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{PoisonError, RwLock},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tenancy {
+    // model always lives in its connection's database
+    #[default]
+    None,
+    // model lives in a database named after the current tenant
+    Database,
+    // model shares a collection, with rows owned by the tenant stored in this field
+    Field(&'static str),
+}
+
+tokio::task_local! {
+    static TENANT: String;
+}
+
+lazy_static! {
+    // (connection name, tenant) -> database handle
+    static ref DATABASES: RwLock<HashMap<(String, String), Database>> = RwLock::new(HashMap::new());
+}
+
+// runs `future` with `tenant` as the current tenant
+// the scope does not follow tasks spawned from inside `future`
+pub async fn scope<F: Future>(tenant: impl ToString, future: F) -> F::Output {
+    TENANT.scope(tenant.to_string(), future).await
+}
+
+pub fn current() -> Option<String> {
+    TENANT.try_with(Clone::clone).ok()
+}
+
+fn required<M: Model>() -> Result<String, MongooseError> {
+    current().ok_or_else(|| MongooseError::Tenant(format!("{} requires a tenant scope", M::name())))
+}
+
+// restricts `filter` to the current tenant for field scoped models
+pub(crate) fn filter<M: Model>(filter: Document) -> Result<Document, MongooseError> {
+    let Tenancy::Field(field) = M::tenancy() else {
+        return Ok(filter);
+    };
+    let tenant = required::<M>()?;
+    if filter.contains_key(field) {
+        // never let a caller supplied value widen the scope
+        return Ok(doc! { "$and": [filter, { field: tenant }] });
+    }
+    let mut filter = filter;
+    filter.insert(field, tenant);
+    Ok(filter)
+}
+
+// stages the server requires first, the tenant `$match` goes right after them
+const LEADING_STAGES: [&str; 3] = ["$geoNear", "$search", "$vectorSearch"];
+// first stages whose output is not the collection's documents, so can not be scoped
+const UNSCOPED_STAGES: [&str; 7] = [
+    "$collStats",
+    "$indexStats",
+    "$searchMeta",
+    "$planCacheStats",
+    "$listSearchIndexes",
+    "$changeStream",
+    "$documents",
+];
+
+fn stage_name(stage: &Document) -> Option<&str> {
+    stage.keys().next().map(String::as_str)
+}
+
+// inserts a tenant `$match` stage for field scoped models
+pub(crate) fn pipeline<M: Model>(pipeline: Vec<Document>) -> Result<Vec<Document>, MongooseError> {
+    let Tenancy::Field(field) = M::tenancy() else {
+        return Ok(pipeline);
+    };
+    let tenant = required::<M>()?;
+    let first = pipeline.first().and_then(stage_name);
+    if let Some(stage) = first.filter(|stage| UNSCOPED_STAGES.contains(stage)) {
+        return Err(MongooseError::Tenant(format!(
+            "{stage} can not be scoped to a tenant in {}",
+            M::name()
+        )));
+    }
+    let position = usize::from(first.is_some_and(|stage| LEADING_STAGES.contains(&stage)));
+    let mut pipeline = pipeline;
+    pipeline.insert(position, doc! { "$match": { field: tenant } });
+    Ok(pipeline)
+}
+
+fn tenant_write<M: Model>(field: &str, path: &str) -> MongooseError {
+    MongooseError::Tenant(format!(
+        "update of {path:?} would change the tenant field {field:?} of {}",
+        M::name()
+    ))
+}
+
+// rejects operator updates touching the tenant field, which would move documents between tenants
+pub(crate) fn updates<M: Model>(updates: Document) -> Result<Document, MongooseError> {
+    let Tenancy::Field(field) = M::tenancy() else {
+        return Ok(updates);
+    };
+    for (operator, fields) in &updates {
+        let Some(fields) = fields.as_document() else {
+            continue;
+        };
+        for (path, value) in fields {
+            // both sides of a rename are written to
+            let target = value.as_str().filter(|_| operator == "$rename");
+            if let Some(path) = std::iter::once(path.as_str())
+                .chain(target)
+                .find(|path| update::overlaps(path, field))
+            {
+                return Err(tenant_write::<M>(field, path));
+            }
+        }
+    }
+    Ok(updates)
+}
+
+// same as `updates` for pipeline updates
+// stages that rebuild the whole document are rejected, they could drop the tenant field
+pub(crate) fn update_pipeline<M: Model>(
+    pipeline: Vec<Document>,
+) -> Result<Vec<Document>, MongooseError> {
+    let Tenancy::Field(field) = M::tenancy() else {
+        return Ok(pipeline);
+    };
+    for stage in &pipeline {
+        for (name, spec) in stage {
+            let paths: Vec<&str> = match (name.as_str(), spec) {
+                ("$set" | "$addFields", Bson::Document(spec)) => {
+                    spec.keys().map(String::as_str).collect()
+                }
+                ("$unset", Bson::String(path)) => vec![path.as_str()],
+                ("$unset", Bson::Array(paths)) => paths.iter().filter_map(Bson::as_str).collect(),
+                ("$project" | "$replaceRoot" | "$replaceWith", _) => {
+                    return Err(MongooseError::Tenant(format!(
+                        "{name} is not allowed in updates of tenant scoped {}",
+                        M::name()
+                    )));
+                }
+                _ => vec![],
+            };
+            if let Some(path) = paths.into_iter().find(|path| update::overlaps(path, field)) {
+                return Err(tenant_write::<M>(field, path));
+            }
+        }
+    }
+    Ok(pipeline)
+}
+
+// change events carry the document under `fullDocument`, which update events only have with
+// `full_document` set, so field scoped models default it to `UpdateLookup`
+// delete events only carry the tenant under `fullDocumentBeforeChange`, they are dropped unless
+// pre-images are enabled on the collection and requested through `full_document_before_change`
+pub(crate) fn change_stream<M: Model>(
+    pipeline: Vec<Document>,
+    options: Option<ChangeStreamOptions>,
+) -> Result<(Vec<Document>, Option<ChangeStreamOptions>), MongooseError> {
+    let Tenancy::Field(field) = M::tenancy() else {
+        return Ok((pipeline, options));
+    };
+    let tenant = required::<M>()?;
+    let mut options = options.unwrap_or_default();
+    options
+        .full_document
+        .get_or_insert(FullDocumentType::UpdateLookup);
+    let scope = match options.full_document_before_change {
+        None | Some(FullDocumentBeforeChangeType::Off) => {
+            doc! { format!("fullDocument.{field}"): tenant }
+        }
+        Some(_) => doc! {
+            "$or": [
+                { format!("fullDocument.{field}"): &tenant },
+                { format!("fullDocumentBeforeChange.{field}"): &tenant },
+            ]
+        },
+    };
+    let pipeline = std::iter::once(doc! { "$match": scope })
+        .chain(pipeline)
+        .collect();
+    Ok((pipeline, Some(options)))
+}
+
+// serializes `model` with the current tenant written into its tenant field
+// returns `None` for models that are not field scoped
+pub(crate) fn stamp<M: Model>(model: &M) -> Result<Option<Document>, MongooseError> {
+    let Tenancy::Field(field) = M::tenancy() else {
+        return Ok(None);
+    };
+    let tenant = required::<M>()?;
+    let mut document = bson::to_document(model).map_err(MongooseError::insert_one)?;
+    document.insert(field, tenant);
+    Ok(Some(document))
+}
+
+// databases the server keeps for itself
+const RESERVED: [&str; 3] = ["admin", "config", "local"];
+
+// rejects names the server refuses, or that would resolve to a system database
+pub fn validate_database_name(name: &str) -> Result<(), MongooseError> {
+    if name.is_empty() || name.len() >= 64 {
+        return Err(MongooseError::Tenant(format!(
+            "tenant database name {name:?} must be 1 to 63 bytes"
+        )));
+    }
+    if RESERVED.contains(&name) {
+        return Err(MongooseError::Tenant(format!(
+            "tenant database name {name:?} is reserved"
+        )));
+    }
+    if let Some(invalid) = name.chars().find(|c| "/\\. \"$*<>:|?\0".contains(*c)) {
+        return Err(MongooseError::Tenant(format!(
+            "tenant database name {name:?} contains {invalid:?}"
+        )));
+    }
+    Ok(())
+}
+
+// `name` is the tenant's database as mapped by `Model::tenant_database`
+pub(crate) fn database(
+    connection_name: &str,
+    connection: &Connection,
+    name: &str,
+) -> Result<Database, MongooseError> {
+    let key = (connection_name.to_string(), name.to_string());
+    if let Some(database) = DATABASES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&key)
+    {
+        return Ok(database.clone());
+    }
+    validate_database_name(name)?;
+    Ok(DATABASES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(key)
+        .or_insert_with(|| connection.client.database(name))
+        .clone())
+}
+
+// drops cached handles when a connection is replaced
+pub(crate) fn evict(connection_name: &str) {
+    DATABASES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|(name, _), _| name != connection_name);
+}
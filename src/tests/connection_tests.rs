@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod connection {
+    use crate::tests::mock;
+    use crate::types::MongooseError;
+    use crate::{connection, doc, Config, DateTime, Model};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    struct Report {
+        #[serde(rename = "_id")]
+        id: String,
+        title: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    }
+
+    impl Default for Report {
+        fn default() -> Self {
+            Self {
+                id: Self::generate_nanoid(),
+                title: String::new(),
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            }
+        }
+    }
+
+    impl Model for Report {
+        fn connection_name() -> &'static str {
+            "analytics"
+        }
+        fn database_name() -> Option<&'static str> {
+            Some("mongoose-rs-analytics")
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_connection() -> Result<(), MongooseError> {
+        let missing = connection::get_named("not-registered");
+        assert!(matches!(missing, Err(MongooseError::Connection(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn named_connection() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        if !connection::is_registered("analytics") {
+            crate::init_named("analytics", Config::from_env()).await?;
+        }
+        let report = Report {
+            title: mock::nanoid(),
+            ..Default::default()
+        }
+        .save()
+        .await?;
+        assert_eq!(Report::database().await?.name(), "mongoose-rs-analytics");
+        let found = Report::read(doc! { "title": &report.title }).await?;
+        assert_eq!(found.id, report.id);
+        Ok(())
+    }
+}
@@ -5,6 +5,7 @@ mod create {
 
     #[tokio::test]
     async fn create_one() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let new_user = mock::user().save().await;
         assert!(new_user.is_ok());
         Ok(())
@@ -12,6 +13,7 @@ mod create {
 
     #[tokio::test]
     async fn bulk_insert() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let users = (0..5).into_iter().map(|_| mock::user()).collect::<Vec<_>>();
         let inserted = User::bulk_insert(&users).await?;
         assert!(inserted.inserted_ids.len() == 5);
@@ -20,6 +22,7 @@ mod create {
 
     #[tokio::test]
     async fn create_one_with_relation() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let new_user = mock::user();
         let inserted = new_user.save().await?;
         assert_eq!(inserted.username, new_user.username);
@@ -33,6 +36,7 @@ mod create {
 
     #[tokio::test]
     async fn bulk_create_with_relation() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let new_user = mock::user();
         let inserted = new_user.save().await?;
         assert_eq!(inserted.username, new_user.username);
@@ -48,6 +52,7 @@ mod create {
 
     #[tokio::test]
     async fn create_indexes() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let indexes = &[
             IndexModel::builder()
                 .keys(doc! { "username": 1 })
@@ -67,7 +72,7 @@ mod create {
                 .build(),
         ];
         let created_names = User::create_indexes(indexes).await?.index_names;
-        let names = User::collection().await.list_index_names().await.unwrap();
+        let names = User::collection().await?.list_index_names().await.unwrap();
         created_names
             .iter()
             .for_each(|name| assert!(names.contains(name)));
@@ -77,6 +82,7 @@ mod create {
     #[ignore = "run this last, since its so long"]
     #[tokio::test]
     async fn create_ttl_indexes() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let indexes = &[IndexModel::builder()
             .keys(doc! { "created_at": 1 })
             .options(
@@ -6,6 +6,7 @@ mod delete {
 
     #[tokio::test]
     async fn delete_one() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let inserted = mock::user().save().await?;
         let found = User::read_by_id(&inserted.id).await?;
         assert_eq!(found.id, inserted.id);
@@ -20,6 +21,7 @@ mod delete {
 
     #[tokio::test]
     async fn bulk_delete() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let users = (0..10)
             .into_iter()
             .map(|_| mock::user())
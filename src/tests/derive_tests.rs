@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod derive {
+    use crate::tests::mock;
+    use crate::types::MongooseError;
+    use crate::validation::Validate;
+    use crate::{DateTime, Model};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Model)]
+    #[model(collection = "people", timestamps, id = "nanoid")]
+    struct Person {
+        #[serde(rename = "_id")]
+        id: String,
+        #[index(unique)]
+        email: String,
+        #[index(desc, name = "age_desc")]
+        age: u32,
+        nickname: Option<String>,
+        created_at: DateTime,
+        updated_at: DateTime,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default, Model)]
+    #[model(naming = "inflected")]
+    struct Category {
+        label: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default, Validate)]
+    struct Location {
+        #[validate(one_of("US", "CA"))]
+        country: String,
+        #[validate(regex = "^[0-9]{5}$")]
+        zip: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default, Model)]
+    struct Member {
+        #[serde(rename = "handle")]
+        #[validate(length(min = 3, max = 16))]
+        username: String,
+        #[validate(email)]
+        email: String,
+        #[validate(range(min = 13, max = 130))]
+        age: u32,
+        #[validate(length(max = 4))]
+        nickname: Option<String>,
+        #[validate(nested)]
+        location: Location,
+    }
+
+    #[tokio::test]
+    async fn derived_validation() -> Result<(), MongooseError> {
+        let member = Member {
+            username: "jude".to_string(),
+            email: "jude@mail.com".to_string(),
+            age: 30,
+            nickname: None,
+            location: Location {
+                country: "US".to_string(),
+                zip: "10001".to_string(),
+            },
+        };
+        assert!(member.validate().is_ok());
+        assert!(member.location.validate().is_ok());
+
+        let invalid = Member {
+            username: "j".to_string(),
+            email: "not-an-email".to_string(),
+            age: 7,
+            nickname: Some("too long".to_string()),
+            location: Location {
+                country: "FR".to_string(),
+                zip: "ABCDE".to_string(),
+            },
+        };
+        let errors = invalid.validate().unwrap_err();
+        let paths = errors
+            .errors
+            .iter()
+            .map(|error| (error.path.as_str(), error.code.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                ("handle", "length"),
+                ("email", "email"),
+                ("age", "range"),
+                ("nickname", "length"),
+                ("location.country", "one_of"),
+                ("location.zip", "regex"),
+            ]
+        );
+        // validation runs before anything touches the database
+        assert!(matches!(
+            invalid.save().await,
+            Err(MongooseError::Validation(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn derived_naming() -> Result<(), MongooseError> {
+        assert_eq!(Category::name(), "categories");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn derived_model() -> Result<(), MongooseError> {
+        assert_eq!(Person::name(), "people");
+        let person = Person::default();
+        assert_eq!(person.id.len(), 20);
+        assert!(person.nickname.is_none());
+        assert!(person.created_at.timestamp_millis() > 0);
+        let indexes = Person::indexes();
+        assert_eq!(indexes.len(), 2);
+        assert_eq!(indexes[0].keys, crate::doc! { "email": 1 });
+        assert_eq!(
+            indexes[0]
+                .options
+                .as_ref()
+                .and_then(|options| options.unique),
+            Some(true)
+        );
+        assert_eq!(indexes[1].keys, crate::doc! { "age": -1 });
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn derived_indexes() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let created = Person::create_indexes(&Person::indexes()).await?;
+        assert!(created.index_names.contains(&"age_desc".to_string()));
+        let person = Person {
+            email: format!("{}@mail.com", mock::nanoid()),
+            ..Default::default()
+        }
+        .save()
+        .await?;
+        assert_eq!(Person::read_by_id(&person.id).await?.email, person.email);
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod drift {
+    use crate::schema::SchemaReport;
+    use crate::tests::mock::{self, User};
+    use crate::types::MongooseError;
+    use crate::{doc, Document, Model};
+    use bson::Bson;
+    use serde::{Deserialize, Serialize};
+
+    fn user_document() -> Document {
+        bson::to_document(&mock::user()).unwrap()
+    }
+
+    #[test]
+    fn groups_failures() {
+        let mut report = SchemaReport::default();
+        report.check::<User>(user_document());
+        for id in ["a", "b", "c"] {
+            let mut document = user_document();
+            document.insert("_id", id);
+            document.get_document_mut("address").unwrap().remove("city");
+            report.check::<User>(document);
+        }
+        let mut document = user_document();
+        document.insert("_id", "d");
+        document.insert("age", "thirty");
+        report.check::<User>(document);
+
+        let report = report.finish();
+        assert_eq!(report.scanned, 5);
+        assert_eq!(report.failed, 4);
+        assert!(!report.is_clean());
+        assert_eq!(report.failures.len(), 2);
+        assert_eq!(report.failures[0].path, "address");
+        assert_eq!(report.failures[0].message, "missing field `city`");
+        assert_eq!(report.failures[0].count, 3);
+        assert_eq!(
+            report.failures[0].examples,
+            vec![Bson::from("a"), Bson::from("b"), Bson::from("c")]
+        );
+        assert_eq!(report.failures[1].path, "age");
+        assert_eq!(report.failures[1].examples, vec![Bson::from("d")]);
+    }
+
+    // a collection of its own, other tests list users while this one holds a broken document
+    #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+    struct Gadget {
+        #[serde(rename = "_id")]
+        id: String,
+        example_array: Vec<u32>,
+    }
+
+    impl Model for Gadget {
+        const COLLECTION_NAME: Option<&'static str> = Some("drift_gadgets");
+    }
+
+    #[tokio::test]
+    async fn check_schema() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let gadget = Gadget {
+            id: mock::nanoid(),
+            example_array: vec![1, 2, 3],
+        }
+        .save()
+        .await?;
+        let collection = Gadget::collection().await?.clone_with_type::<Document>();
+        let broken_id = mock::nanoid();
+        collection
+            .insert_one(
+                doc! { "_id": &broken_id, "example_array": "not an array" },
+                None,
+            )
+            .await
+            .map_err(MongooseError::schema)?;
+
+        let report = Gadget::check_schema(None).await;
+        let sampled = Gadget::check_schema(Some(1)).await;
+        // removed before asserting so a failure does not leave it behind
+        collection
+            .delete_one(doc! { "_id": &broken_id }, None)
+            .await
+            .map_err(MongooseError::schema)?;
+
+        let report = report?;
+        assert!(report.scanned >= 2);
+        let failure = report
+            .failures
+            .iter()
+            .find(|failure| failure.path == "example_array")
+            .unwrap();
+        assert!(failure.examples.len() <= 5);
+        assert!(sampled?.scanned <= 1);
+        assert!(Gadget::read_by_id(&gadget.id).await.is_ok());
+        Ok(())
+    }
+}
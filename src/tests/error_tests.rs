@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod errors {
+    use crate::doc;
+    use crate::tests::mock::User;
+    use crate::types::{DuplicateKey, MongooseError};
+    use mongodb::error::{CommandError, Error, ErrorKind, WriteError, WriteFailure};
+
+    fn write_error(code: i32, message: &str) -> Error {
+        let write =
+            bson::from_document::<WriteError>(doc! { "code": code, "errmsg": message }).unwrap();
+        Error::from(ErrorKind::Write(WriteFailure::WriteError(write)))
+    }
+
+    fn command_error(code: i32) -> Error {
+        let command =
+            bson::from_document::<CommandError>(doc! { "code": code, "errmsg": "failed" }).unwrap();
+        Error::from(ErrorKind::Command(command))
+    }
+
+    #[test]
+    fn duplicate_key() {
+        let err = MongooseError::insert_one(write_error(
+            11000,
+            "E11000 duplicate key error collection: db.users index: username_1 dup key: { username: \"jude\" }",
+        ));
+        assert!(matches!(err, MongooseError::InsertOne(_)));
+        assert!(err.is_duplicate_key());
+        assert_eq!(
+            err.duplicate_key(),
+            Some(DuplicateKey {
+                index: "username_1".to_string(),
+                key: "{ username: \"jude\" }".to_string(),
+            })
+        );
+        assert!(!err.is_timeout());
+        assert!(!err.is_retryable());
+        // the driver error is the `source`, as it is for `error_source`
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.downcast_ref::<Error>().is_some());
+        assert!(err.error_source().unwrap().inner().unwrap().is::<Error>());
+        // and error reporters walking `source` print its message once
+        let mut chain = vec![err.to_string()];
+        let mut cause = std::error::Error::source(&err);
+        while let Some(error) = cause {
+            chain.push(error.to_string());
+            cause = error.source();
+        }
+        assert_eq!(
+            chain
+                .iter()
+                .filter(|message| message.contains("E11000"))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn classification() {
+        let conflict = MongooseError::update(command_error(112));
+        assert!(conflict.is_write_conflict());
+        assert!(!conflict.is_duplicate_key());
+
+        let expired = MongooseError::list(command_error(50));
+        assert!(expired.is_timeout());
+
+        let io = std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out");
+        let timeout = MongooseError::count(Error::from(ErrorKind::Io(io.into())));
+        assert!(timeout.is_timeout());
+        assert!(timeout.is_retryable());
+
+        let stepdown = MongooseError::bulk_update(command_error(91));
+        assert!(stepdown.is_retryable());
+
+        let hook = MongooseError::Hook("nope".to_string());
+        assert!(hook.driver_error().is_none());
+        assert!(!hook.is_retryable());
+    }
+
+    #[test]
+    fn deserialize_kind() {
+        let bson_error = bson::from_document::<User>(doc! { "_id": 1 }).unwrap_err();
+        assert!(MongooseError::list(bson_error).is_deserialize());
+        let bson_error = bson::from_document::<User>(doc! { "_id": 1 }).unwrap_err();
+        let driver_error = Error::from(ErrorKind::BsonDeserialization(bson_error));
+        assert!(matches!(
+            MongooseError::aggregate(driver_error),
+            MongooseError::Deserialize(_)
+        ));
+    }
+
+    #[test]
+    fn serializes_message() {
+        let err = MongooseError::count(command_error(50));
+        let json = serde_json::to_value(&err).unwrap();
+        let restored = serde_json::from_value::<MongooseError>(json).unwrap();
+        assert_eq!(restored.to_string(), err.to_string());
+        assert!(restored.driver_error().is_none());
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod fields {
+    use crate::fields::{FieldPath, Fields};
+    use crate::tests::mock;
+    use crate::types::MongooseError;
+    use crate::{doc, DateTime, Filter, Model, Update};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default, Fields)]
+    struct Address {
+        city: String,
+        #[serde(rename = "zip_code")]
+        zip: String,
+        apt_number: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Model, Fields)]
+    #[model(timestamps, id = "nanoid")]
+    struct Member {
+        #[serde(rename = "_id")]
+        id: String,
+        username: String,
+        age: u32,
+        #[fields(nested)]
+        address: Address,
+        #[fields(nested)]
+        previous: Vec<Address>,
+        example_array: Vec<u32>,
+        created_at: DateTime,
+        updated_at: DateTime,
+    }
+
+    #[test]
+    fn generated_paths() {
+        assert_eq!(Member::fields().id().as_str(), "_id");
+        assert_eq!(Member::fields().username().as_str(), "username");
+        assert_eq!(Member::fields().address().as_ref(), "address");
+        assert_eq!(Member::fields().address().city().as_str(), "address.city");
+        assert_eq!(
+            Member::fields().address().zip().as_str(),
+            "address.zip_code"
+        );
+        assert_eq!(Member::fields().previous().city().as_str(), "previous.city");
+        assert_eq!(
+            Member::fields().example_array().join("$"),
+            FieldPath::new("example_array.$")
+        );
+        assert_eq!(Address::fields().city().to_string(), "city");
+        assert_eq!(Address::paths("home").city().to_string(), "home.city");
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default, Fields)]
+    struct Audit {
+        changed_by: String,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default, Fields)]
+    #[serde(rename_all = "camelCase")]
+    struct Invoice {
+        due_date: String,
+        #[serde(rename(serialize = "total", deserialize = "amount"))]
+        total_cents: u64,
+        #[serde(flatten)]
+        #[fields(nested)]
+        audit: Audit,
+        #[serde(flatten)]
+        #[fields(skip)]
+        extra: std::collections::HashMap<String, String>,
+    }
+
+    #[test]
+    fn serde_renames() {
+        assert_eq!(Invoice::fields().due_date().as_str(), "dueDate");
+        assert_eq!(Invoice::fields().total_cents().as_str(), "total");
+        assert_eq!(
+            Invoice::fields().audit().changed_by().as_str(),
+            "changed_by"
+        );
+        assert_eq!(
+            Invoice::paths("billing").audit().changed_by().as_str(),
+            "billing.changed_by"
+        );
+    }
+
+    #[test]
+    fn paths_as_keys() {
+        let fields = Member::fields();
+        let (filter, options) = Member::find()
+            .field(fields.age())
+            .gte(18)
+            .field(fields.address().city())
+            .eq("Denver")
+            .or([Filter::new().field(fields.username()).exists(true)])
+            .sort_desc(fields.created_at())
+            .select([fields.username(), fields.address().zip()])
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter,
+            doc! {
+                "age": { "$gte": 18 },
+                "address.city": { "$eq": "Denver" },
+                "$or": [{ "username": { "$exists": true } }],
+            }
+        );
+        assert_eq!(options.sort, doc! { "created_at": -1 });
+        assert_eq!(
+            options.projection,
+            Some(doc! { "username": 1, "address.zip_code": 1 })
+        );
+        let updates = Update::new()
+            .set(fields.address().city(), "Boulder")
+            .inc(fields.age(), 1)
+            .rename(
+                fields.address().zip(),
+                FieldPath::under(fields.address().as_ref(), "postal"),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(
+            updates
+                .get_document("$set")
+                .unwrap()
+                .get_str("address.city")
+                .unwrap(),
+            "Boulder"
+        );
+        assert_eq!(updates.get_document("$inc").unwrap(), &doc! { "age": 1 });
+        assert_eq!(
+            updates.get_document("$rename").unwrap(),
+            &doc! { "address.zip_code": "address.postal" }
+        );
+        assert_eq!(
+            doc! { (fields.address().city()): "Denver" },
+            doc! { "address.city": "Denver" }
+        );
+    }
+
+    #[tokio::test]
+    async fn filter_with_paths() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let member = Member {
+            username: mock::nanoid(),
+            age: mock::number(),
+            address: Address {
+                city: mock::nanoid(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        member.save().await?;
+        let fields = Member::fields();
+        let found = Member::read(doc! {
+            (fields.address().city()): &member.address.city,
+            (fields.username()): &member.username,
+        })
+        .await?;
+        assert_eq!(found.id, member.id);
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod hooks {
+    use crate::tests::mock;
+    use crate::types::MongooseError;
+    use crate::{doc, Model};
+    use bson::{Bson, Document};
+    use mongodb::results::DeleteResult;
+    use serde::{Deserialize, Serialize};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static SAVED: AtomicUsize = AtomicUsize::new(0);
+    static PIPELINES: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+    struct Account {
+        #[serde(rename = "_id")]
+        id: String,
+        email: String,
+    }
+
+    impl Model for Account {
+        async fn pre_save(&mut self) -> Result<(), MongooseError> {
+            if self.email.trim().is_empty() {
+                return Err(MongooseError::Hook("email is required".to_string()));
+            }
+            self.email = self.email.trim().to_lowercase();
+            Ok(())
+        }
+
+        async fn post_save(&self) -> Result<(), MongooseError> {
+            SAVED.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn pre_update(
+            _filter: &mut Document,
+            updates: &mut Document,
+        ) -> Result<(), MongooseError> {
+            if let Ok(email) = updates.get_str("email") {
+                let email = email.trim().to_lowercase();
+                updates.insert("email", email);
+            }
+            Ok(())
+        }
+
+        async fn pre_update_pipeline(
+            filter: &mut Document,
+            _pipeline: &mut Vec<Document>,
+        ) -> Result<(), MongooseError> {
+            if filter.is_empty() {
+                return Err(MongooseError::Hook(
+                    "refusing to update everything".to_string(),
+                ));
+            }
+            Ok(())
+        }
+
+        async fn post_update_pipeline(
+            _filter: &Document,
+            _pipeline: &[Document],
+        ) -> Result<(), MongooseError> {
+            PIPELINES.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn pre_delete(filter: &mut Document) -> Result<(), MongooseError> {
+            if filter.is_empty() {
+                return Err(MongooseError::Hook(
+                    "refusing to delete everything".to_string(),
+                ));
+            }
+            Ok(())
+        }
+
+        async fn post_delete(
+            filter: &Document,
+            result: &DeleteResult,
+        ) -> Result<(), MongooseError> {
+            assert!(filter.contains_key("_id"));
+            assert!(result.deleted_count <= 1);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+    struct Receipt {
+        #[serde(rename = "_id")]
+        id: String,
+    }
+
+    impl Model for Receipt {
+        async fn post_save(&self) -> Result<(), MongooseError> {
+            Err(MongooseError::Hook("notification failed".to_string()))
+        }
+    }
+
+    fn account(email: &str) -> Account {
+        Account {
+            id: mock::nanoid(),
+            email: email.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn pre_hooks_abort() {
+        // aborted before any connection is needed
+        let saved = account("   ").save().await;
+        assert!(matches!(saved, Err(MongooseError::Hook(_))));
+        let inserted = Account::bulk_insert(&[account("a@mail.com"), account("")]).await;
+        assert!(matches!(inserted, Err(MongooseError::Hook(_))));
+        let deleted = Account::bulk_delete(doc! {}).await;
+        assert!(matches!(deleted, Err(MongooseError::Hook(_))));
+        let pipeline = vec![doc! { "$set": { "email": { "$toLower": "$email" } } }];
+        let updated = Account::bulk_update_pipeline(doc! {}, pipeline).await;
+        assert!(matches!(updated, Err(MongooseError::Hook(_))));
+    }
+
+    #[tokio::test]
+    async fn hooks_run_around_writes() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let before = SAVED.load(Ordering::SeqCst);
+        let saved = account(" Someone@Mail.COM ").save().await?;
+        assert_eq!(saved.email, "someone@mail.com");
+        assert_eq!(
+            Account::read_by_id(&saved.id).await?.email,
+            "someone@mail.com"
+        );
+
+        Account::bulk_insert(&[account("B@mail.com"), account("C@mail.com")]).await?;
+        assert!(SAVED.load(Ordering::SeqCst) >= before + 3);
+
+        let updated = Account::update(
+            doc! { "_id": &saved.id },
+            doc! { "email": "Other@Mail.com" },
+        )
+        .await?;
+        assert_eq!(updated.email, "other@mail.com");
+
+        let before = PIPELINES.load(Ordering::SeqCst);
+        let pipeline = vec![doc! { "$set": { "email": { "$toUpper": "$email" } } }];
+        let updated = Account::update_pipeline(doc! { "_id": &saved.id }, pipeline).await?;
+        assert_eq!(updated.email, "OTHER@MAIL.COM");
+        assert!(PIPELINES.load(Ordering::SeqCst) > before);
+
+        let deleted = Account::delete(doc! { "_id": Bson::String(saved.id) }).await?;
+        assert_eq!(deleted.deleted_count, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn post_hook_errors_keep_the_write() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        // the insert is committed before post_save runs, so the save still succeeds
+        let receipt = Receipt { id: mock::nanoid() }.save().await?;
+        assert_eq!(Receipt::read_by_id(&receipt.id).await?.id, receipt.id);
+        Ok(())
+    }
+}
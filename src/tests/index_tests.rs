@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod indexes {
+    use crate::indexes::{self, index_name, SyncIndexOptions};
+    use crate::tests::mock;
+    use crate::types::MongooseError;
+    use crate::{doc, DateTime, IndexModel, IndexOptions, Model};
+    use serde::{Deserialize, Serialize};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn index(keys: bson::Document, unique: bool) -> IndexModel {
+        IndexModel::builder()
+            .keys(keys)
+            .options(IndexOptions::builder().unique(unique).build())
+            .build()
+    }
+
+    #[test]
+    fn plan() {
+        assert_eq!(
+            index_name(&index(doc! { "email": 1, "created_at": -1 }, false)),
+            "email_1_created_at_-1"
+        );
+        let id_index = IndexModel::builder()
+            .keys(doc! { "_id": 1 })
+            .options(IndexOptions::builder().name("_id_".to_string()).build())
+            .build();
+        let existing = vec![
+            id_index,
+            index(doc! { "email": 1 }, false),
+            index(doc! { "slug": 1 }, false),
+            index(doc! { "stale": 1 }, false),
+        ];
+        let declared = vec![
+            index(doc! { "email": 1 }, true),
+            index(doc! { "slug": 1 }, false),
+            index(doc! { "username": 1 }, true),
+        ];
+        let plan = indexes::plan(&declared, &existing, false);
+        assert_eq!(plan.report.created, vec!["username_1"]);
+        assert_eq!(plan.report.changed, vec!["email_1"]);
+        assert_eq!(plan.report.unchanged, vec!["slug_1"]);
+        assert_eq!(plan.report.dropped, vec!["stale_1"]);
+        assert_eq!(plan.drop, vec!["email_1", "stale_1"]);
+        assert_eq!(plan.create.len(), 2);
+    }
+
+    // toggles the declared indexes between test phases
+    static UNIQUE_SKU: AtomicBool = AtomicBool::new(false);
+
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    struct Product {
+        #[serde(rename = "_id")]
+        id: String,
+        sku: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    }
+
+    impl Default for Product {
+        fn default() -> Self {
+            Self {
+                id: Self::generate_nanoid(),
+                sku: String::new(),
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            }
+        }
+    }
+
+    impl Model for Product {
+        fn indexes() -> Vec<IndexModel> {
+            if UNIQUE_SKU.load(Ordering::SeqCst) {
+                vec![index(doc! { "sku": 1 }, true)]
+            } else {
+                vec![
+                    index(doc! { "sku": 1 }, false),
+                    index(doc! { "created_at": -1 }, false),
+                ]
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_indexes() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        Product::sync_indexes(SyncIndexOptions::default()).await?;
+        let report = Product::sync_indexes(SyncIndexOptions::default()).await?;
+        assert!(report.is_in_sync());
+        UNIQUE_SKU.store(true, Ordering::SeqCst);
+        let dry_run = Product::sync_indexes(SyncIndexOptions { dry_run: true }).await?;
+        assert_eq!(dry_run.changed, vec!["sku_1"]);
+        assert_eq!(dry_run.dropped, vec!["created_at_-1"]);
+        // dry runs leave the collection untouched
+        let again = Product::sync_indexes(SyncIndexOptions { dry_run: true }).await?;
+        assert_eq!(again, dry_run);
+        Product::sync_indexes(SyncIndexOptions::default()).await?;
+        let names = Product::collection()
+            .await?
+            .list_index_names()
+            .await
+            .unwrap();
+        assert!(!names.contains(&"created_at_-1".to_string()));
+        assert!(Product::sync_indexes(SyncIndexOptions::default())
+            .await?
+            .is_in_sync());
+        Ok(())
+    }
+}
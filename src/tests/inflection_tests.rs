@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod inflection {
+    use crate::inflection::{legacy_pluralize, pluralize, Naming};
+    use crate::tests::mock::{Log, Post, User};
+    use crate::Model;
+    use serde::{Deserialize, Serialize};
+
+    #[test]
+    fn pluralize_words() {
+        let cases = [
+            ("user", "users"),
+            ("category", "categories"),
+            ("day", "days"),
+            ("address", "addresses"),
+            ("status", "statuses"),
+            ("person", "people"),
+            ("sales_person", "sales_people"),
+            ("human", "humans"),
+            ("box", "boxes"),
+            ("batch", "batches"),
+            ("wish", "wishes"),
+            ("analysis", "analyses"),
+            ("knife", "knives"),
+            ("wife", "wives"),
+            ("cafe", "cafes"),
+            ("safe", "safes"),
+            ("axis", "axes"),
+            ("shelf", "shelves"),
+            ("child", "children"),
+            ("user_posts", "user_posts"),
+            ("people", "people"),
+            ("news", "news"),
+            ("metadata", "metadata"),
+        ];
+        for (singular, plural) in cases {
+            assert_eq!(pluralize(singular), plural, "pluralizing {singular}");
+        }
+        assert_eq!(legacy_pluralize("category"), "categorys");
+        assert_eq!(legacy_pluralize("status"), "status");
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+    struct Category {}
+
+    impl Model for Category {}
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+    struct InflectedCategory {}
+
+    impl Model for InflectedCategory {
+        const NAMING: Naming = Naming::Inflected;
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+    struct Person {}
+
+    impl Model for Person {
+        const COLLECTION_NAME: Option<&'static str> = Some("humans");
+    }
+
+    #[test]
+    fn model_names() {
+        assert_eq!(User::name(), "users");
+        assert_eq!(Post::name(), "posts");
+        assert_eq!(Log::name(), "logs");
+        // existing collection names are kept unless a model opts in
+        assert_eq!(Category::name(), "categorys");
+        assert_eq!(InflectedCategory::name(), "inflected_categories");
+        assert_eq!(Person::name(), "humans");
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod migrations {
+    use crate::migrations::{Migration, Migrator};
+    use crate::tests::mock;
+    use crate::types::MongooseError;
+    use crate::{connection, doc};
+    use bson::{DateTime, Document};
+    use std::time::Duration;
+
+    fn widgets_migrator(database: &str) -> Migrator {
+        Migrator::new()
+            .database(database)
+            .register(
+                Migration::new("0001_seed", |db| async move {
+                    db.collection::<Document>("widgets")
+                        .insert_one(doc! { "_id": "a", "name": "Widget" }, None)
+                        .await
+                        .map_err(MongooseError::migration)?;
+                    Ok(())
+                })
+                .down(|db| async move {
+                    db.collection::<Document>("widgets")
+                        .delete_one(doc! { "_id": "a" }, None)
+                        .await
+                        .map_err(MongooseError::migration)?;
+                    Ok(())
+                }),
+            )
+            .register(
+                Migration::new("0002_lowercase", |db| async move {
+                    db.collection::<Document>("widgets")
+                        .update_many(
+                            doc! {},
+                            vec![doc! { "$set": { "name": { "$toLower": "$name" } } }],
+                            None,
+                        )
+                        .await
+                        .map_err(MongooseError::migration)?;
+                    Ok(())
+                })
+                .description("lowercase widget names"),
+            )
+    }
+
+    #[test]
+    fn checksum() {
+        let migration = Migration::new("0001_seed", |_| async { Ok(()) });
+        assert_eq!(migration.checksum().len(), 16);
+        assert_eq!(
+            migration.checksum(),
+            Migration::new("0001_seed", |_| async { Ok(()) }).checksum()
+        );
+        assert_ne!(
+            migration.checksum(),
+            Migration::new("0001_seed", |_| async { Ok(()) })
+                .source("v2")
+                .checksum()
+        );
+    }
+
+    #[tokio::test]
+    async fn up_and_down() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let database = format!("mongoose-rs-migrations-{}", mock::nanoid());
+        let migrator = widgets_migrator(&database);
+        assert_eq!(
+            migrator.pending().await?,
+            vec!["0001_seed", "0002_lowercase"]
+        );
+
+        assert_eq!(migrator.up().await?, vec!["0001_seed", "0002_lowercase"]);
+        assert!(migrator.up().await?.is_empty());
+        assert!(migrator.pending().await?.is_empty());
+        let applied = migrator.applied().await?;
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied[1].description, "lowercase widget names");
+
+        let db = connection::get()?.client.database(&database);
+        let widget = db
+            .collection::<Document>("widgets")
+            .find_one(doc! { "_id": "a" }, None)
+            .await
+            .map_err(MongooseError::migration)?;
+        assert_eq!(widget.unwrap().get_str("name").unwrap(), "widget");
+
+        // the latest migration has no down
+        assert!(migrator.down(1).await.is_err());
+
+        let changed =
+            widgets_migrator(&database).register(Migration::new("0003_noop", |_| async { Ok(()) }));
+        assert_eq!(changed.pending().await?, vec!["0003_noop"]);
+        let tampered = Migrator::new()
+            .database(&database)
+            .register(Migration::new("0001_seed", |_| async { Ok(()) }).source("edited"));
+        assert!(tampered.up().await.is_err());
+
+        db.drop(None).await.map_err(MongooseError::migration)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lock_is_exclusive() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let database = format!("mongoose-rs-migrations-{}", mock::nanoid());
+        let db = connection::get()?.client.database(&database);
+        let expires_at = DateTime::from_millis(DateTime::now().timestamp_millis() + 60_000);
+        db.collection::<Document>("_migrations")
+            .insert_one(
+                doc! { "_id": "__lock__", "locked": true, "owner": "other", "expires_at": expires_at },
+                None,
+            )
+            .await
+            .map_err(MongooseError::migration)?;
+
+        let migrator = widgets_migrator(&database).lock_timeout(Duration::from_millis(100));
+        assert!(migrator.up().await.is_err());
+        assert_eq!(migrator.pending().await?.len(), 2);
+
+        // an expired lock is taken over
+        db.collection::<Document>("_migrations")
+            .update_one(
+                doc! { "_id": "__lock__" },
+                doc! { "$set": { "expires_at": DateTime::from_millis(0) } },
+                None,
+            )
+            .await
+            .map_err(MongooseError::migration)?;
+        assert_eq!(migrator.up().await?.len(), 2);
+
+        db.drop(None).await.map_err(MongooseError::migration)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lock_is_renewed() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let database = format!("mongoose-rs-migrations-{}", mock::nanoid());
+        // runs well past the ttl of its lock
+        let slow = Migrator::new()
+            .database(&database)
+            .lock_ttl(Duration::from_millis(600))
+            .register(Migration::new("0001_slow", |_| async {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+                Ok(())
+            }));
+        let other = widgets_migrator(&database).lock_timeout(Duration::from_millis(1500));
+        let (slow, other) = futures::join!(slow.up(), async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            other.up().await
+        });
+        assert_eq!(slow?, vec!["0001_slow"]);
+        assert!(matches!(other, Err(MongooseError::Migration(_))));
+
+        // the migration's own error is returned, not hidden by the unlock
+        let failing = Migrator::new()
+            .database(&database)
+            .register(Migration::new("0002_fails", |_| async {
+                Err(MongooseError::Migration("boom".into()))
+            }));
+        match failing.up().await {
+            Err(MongooseError::Migration(source)) => assert_eq!(source.message(), "boom"),
+            other => panic!("expected the migration error, got {other:?}"),
+        }
+
+        let db = connection::get()?.client.database(&database);
+        db.drop(None).await.map_err(MongooseError::migration)?;
+        Ok(())
+    }
+}
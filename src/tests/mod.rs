@@ -1,14 +1,40 @@
+pub mod connection_tests;
 pub mod create_tests;
 pub mod delete_tests;
+#[cfg(feature = "derive")]
+pub mod derive_tests;
+pub mod drift_tests;
+pub mod error_tests;
+#[cfg(feature = "derive")]
+pub mod fields_tests;
+pub mod hook_tests;
+pub mod index_tests;
+pub mod inflection_tests;
+pub mod migration_tests;
+pub mod pagination_tests;
+pub mod query_tests;
 pub mod read_tests;
+#[cfg(feature = "derive")]
+pub mod schema_tests;
+pub mod tenant_tests;
+pub mod transaction_tests;
 pub mod update_tests;
+pub mod validation_tests;
 pub mod view_tests;
+pub mod watch_tests;
 
 #[cfg(test)]
 mod mock {
-    use crate::{doc, DateTime, Model, Uuid};
+    use crate::{connection, doc, types::MongooseError, Config, DateTime, Model, Uuid};
     use serde::{Deserialize, Serialize};
 
+    pub async fn connect() -> Result<(), MongooseError> {
+        if connection::is_initialized() {
+            return Ok(());
+        }
+        crate::init(Config::from_env()).await
+    }
+
     #[derive(Debug, Deserialize, Serialize, Clone, Default)]
     pub struct Address {
         pub address: u32,
@@ -0,0 +1,189 @@
+#[cfg(test)]
+mod pagination {
+    use crate::pagination::{self, Cursor, Direction, KeysetOptions};
+    use crate::tests::mock::{self, User};
+    use crate::types::{ListOptions, MongooseError};
+    use crate::{doc, Model};
+
+    #[tokio::test]
+    async fn cursor_round_trip() -> Result<(), MongooseError> {
+        let keys = pagination::sort_keys(&doc! { "age": -1, "address.city": 1 })?;
+        assert_eq!(keys.last(), Some(&("_id".to_string(), 1)));
+        let document = doc! { "_id": "abc", "age": 30, "address": { "city": "LA" } };
+        let cursor = Cursor::at(Direction::After, &keys, &document);
+        assert_eq!(Cursor::decode(&cursor.encode()?)?, cursor);
+        assert!(Cursor::decode("not a cursor").is_err());
+        assert!(pagination::sort_keys(&doc! { "age": "asc" }).is_err());
+        let empty = User::keyset_paginate(
+            doc! {},
+            KeysetOptions {
+                limit: 0,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(matches!(empty, Err(MongooseError::Pagination(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn compound_range_filter() -> Result<(), MongooseError> {
+        let keys = pagination::sort_keys(&doc! { "age": -1 })?;
+        let after = Cursor::at(Direction::After, &keys, &doc! { "_id": "abc", "age": 30 });
+        assert_eq!(
+            pagination::range_filter(&keys, &after)?,
+            doc! { "$or": [
+                { "$or": [{ "age": { "$lt": 30 } }, { "age": null }] },
+                { "age": 30, "$or": [{ "_id": { "$lt": "abc" } }, { "_id": null }] },
+            ] }
+        );
+        let before = Cursor {
+            direction: Direction::Before,
+            ..after
+        };
+        assert_eq!(
+            pagination::range_filter(&keys, &before)?,
+            doc! { "$or": [
+                { "age": { "$gt": 30 } },
+                { "age": 30, "_id": { "$gt": "abc" } },
+            ] }
+        );
+        assert_eq!(
+            pagination::sort(&keys, Direction::Before),
+            doc! { "age": 1, "_id": 1 }
+        );
+        // documents without the sort key sort first
+        let missing = Cursor::at(Direction::Before, &keys, &doc! { "_id": "abc" });
+        assert_eq!(
+            pagination::range_filter(&keys, &missing)?,
+            doc! { "$or": [
+                { "age": { "$ne": null } },
+                { "age": null, "_id": { "$gt": "abc" } },
+            ] }
+        );
+        let missing = Cursor {
+            direction: Direction::After,
+            ..missing
+        };
+        assert_eq!(
+            pagination::range_filter(&keys, &missing)?,
+            doc! { "$or": [{ "age": null, "$or": [{ "_id": { "$lt": "abc" } }, { "_id": null }] }] }
+        );
+        // a cursor from another sort is rejected rather than misread
+        let other = pagination::sort_keys(&doc! { "age": -1, "slug": 1 })?;
+        assert!(pagination::range_filter(&other, &before).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn facet_limits() {
+        let items = |limit| {
+            pagination::facet_pipeline(doc! {}, doc! {}, None, 10, limit).map(|pipeline| {
+                pipeline[1]
+                    .get_document("$facet")
+                    .and_then(|facet| facet.get_array("items"))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+        };
+        assert_eq!(
+            items(5).unwrap(),
+            vec![
+                doc! { "$skip": 10_i64 }.into(),
+                doc! { "$limit": 5_i64 }.into()
+            ]
+        );
+        // like `ListOptions`, no limit at all
+        assert_eq!(items(0).unwrap(), vec![doc! { "$skip": 10_i64 }.into()]);
+        assert!(matches!(items(-1), Err(MongooseError::Pagination(_))));
+    }
+
+    #[tokio::test]
+    async fn paginate() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let zip = mock::nanoid();
+        let users = (0..5)
+            .map(|_| {
+                let mut user = mock::user();
+                user.address.zip = zip.clone();
+                user
+            })
+            .collect::<Vec<_>>();
+        User::bulk_insert(&users).await?;
+        let page = User::paginate(
+            doc! { "address.zip": &zip },
+            ListOptions {
+                limit: 2,
+                skip: 2,
+                sort: doc! { "age": 1 },
+                ..Default::default()
+            },
+        )
+        .await?;
+        assert_eq!(page.total, 5);
+        assert_eq!(page.items.len(), 2);
+        assert!(page.has_next);
+        assert!(page.has_prev);
+        assert!(page.items[0].age <= page.items[1].age);
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(json["total"], 5);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keyset_paginate() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let zip = mock::nanoid();
+        let users = (0..5)
+            .map(|_| {
+                let mut user = mock::user();
+                user.address.zip = zip.clone();
+                user
+            })
+            .collect::<Vec<_>>();
+        User::bulk_insert(&users).await?;
+        let filter = doc! { "address.zip": &zip };
+        let options = KeysetOptions {
+            limit: 2,
+            sort: doc! { "age": -1 },
+            ..Default::default()
+        };
+        let first = User::keyset_paginate(filter.clone(), options.clone()).await?;
+        assert_eq!(first.items.len(), 2);
+        assert!(first.prev_cursor.is_none());
+        let second = User::keyset_paginate(
+            filter.clone(),
+            KeysetOptions {
+                cursor: first.next_cursor.clone(),
+                ..options.clone()
+            },
+        )
+        .await?;
+        assert_eq!(second.items.len(), 2);
+        assert!(first.items[1].age >= second.items[0].age);
+        let third = User::keyset_paginate(
+            filter.clone(),
+            KeysetOptions {
+                cursor: second.next_cursor.clone(),
+                ..options.clone()
+            },
+        )
+        .await?;
+        assert_eq!(third.items.len(), 1);
+        assert!(third.next_cursor.is_none());
+        // walking back lands on the same second page
+        let back = User::keyset_paginate(
+            filter,
+            KeysetOptions {
+                cursor: third.prev_cursor.clone(),
+                ..options
+            },
+        )
+        .await?;
+        let back_ids = back.items.iter().map(|user| &user.id).collect::<Vec<_>>();
+        let second_ids = second.items.iter().map(|user| &user.id).collect::<Vec<_>>();
+        assert_eq!(back_ids, second_ids);
+        assert!(back.prev_cursor.is_some());
+        Ok(())
+    }
+}
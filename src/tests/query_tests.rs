@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod query {
+    use crate::tests::mock::{self, User};
+    use crate::types::MongooseError;
+    use crate::{doc, Filter, Model, Regex};
+    use futures::TryStreamExt;
+    use std::collections::HashMap;
+
+    #[test]
+    fn compiles_filters() {
+        let (filter, options) = User::find()
+            .field("age")
+            .gt(18)
+            .field("address.state")
+            .eq("CA")
+            .field("username")
+            .regex("^user", "xi")
+            .field("example_array")
+            .size(3)
+            .or([
+                Filter::new().field("slug").exists(true),
+                Filter::new()
+                    .field("email")
+                    .is_in(["a@mail.com", "b@mail.com"]),
+            ])
+            .sort_desc("created_at")
+            .limit(10)
+            .skip(5)
+            .select(["username", "age"])
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter,
+            doc! {
+                "age": { "$gt": 18 },
+                "address.state": { "$eq": "CA" },
+                "username": { "$regex": Regex { pattern: "^user".to_string(), options: "ix".to_string() } },
+                "example_array": { "$size": 3_i64 },
+                "$or": [
+                    { "slug": { "$exists": true } },
+                    { "email": { "$in": ["a@mail.com", "b@mail.com"] } },
+                ],
+            }
+        );
+        assert_eq!(options.sort, doc! { "created_at": -1 });
+        assert_eq!(options.limit, 10);
+        assert_eq!(options.skip, 5);
+        assert_eq!(options.projection, Some(doc! { "username": 1, "age": 1 }));
+    }
+
+    #[test]
+    fn repeated_keys_use_and() {
+        let filter = Filter::new()
+            .field("age")
+            .gte(18)
+            .field("age")
+            .not()
+            .gt(65)
+            .field("example_array")
+            .elem_match(Filter::new().raw(doc! { "$gte": 10, "$lt": 20 }))
+            .nor([Filter::new().field("slug").not_in(["a"])])
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter,
+            doc! {
+                "$and": [
+                    { "age": { "$gte": 18 } },
+                    { "age": { "$not": { "$gt": 65 } } },
+                    { "example_array": { "$elemMatch": { "$gte": 10, "$lt": 20 } } },
+                    { "$nor": [{ "slug": { "$nin": ["a"] } }] },
+                ]
+            }
+        );
+        let filter = Filter::new()
+            .field("tags")
+            .all(["a", "b"])
+            .field("age")
+            .ne(1)
+            .field("score")
+            .lte(2.5)
+            .and([Filter::new().field("x").lt(1)])
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter,
+            doc! {
+                "tags": { "$all": ["a", "b"] },
+                "age": { "$ne": 1 },
+                "score": { "$lte": 2.5 },
+                "$and": [{ "x": { "$lt": 1 } }],
+            }
+        );
+        let invalid = Filter::new()
+            .field("lookup")
+            .eq(HashMap::from([(1, 2)]))
+            .build();
+        assert!(matches!(invalid, Err(MongooseError::InvalidQuery(_))));
+    }
+
+    #[tokio::test]
+    async fn executes() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let user = mock::user().save().await?;
+        let found = User::find()
+            .field("username")
+            .eq(&user.username)
+            .field("age")
+            .gte(0)
+            .sort_desc("created_at")
+            .read()
+            .await?;
+        assert_eq!(found.id, user.id);
+        let by_username = || User::find().field("username").eq(&user.username);
+        assert_eq!(by_username().count().await?, 1);
+        assert_eq!(by_username().list().await?.len(), 1);
+        let streamed = by_username()
+            .stream()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(streamed.len(), 1);
+        assert_eq!(by_username().delete().await?.deleted_count, 1);
+        assert!(by_username().read().await.is_err());
+        Ok(())
+    }
+}
@@ -2,11 +2,13 @@
 mod read {
     use crate::tests::mock::{self, Address, PopulatedPost, Post, User};
     use crate::types::MongooseError;
-    use crate::{doc, types::ListOptions, DateTime, Model};
+    use crate::{doc, types::ListOptions, AggregateOptions, DateTime, Model};
+    use futures::{StreamExt, TryStreamExt};
     use serde::{Deserialize, Serialize};
 
     #[tokio::test]
     async fn read() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let new_user = mock::user().save().await?;
         let user = User::read(doc! { "username": &new_user.username }).await?;
         assert_eq!(user.username, new_user.username);
@@ -15,6 +17,7 @@ mod read {
 
     #[tokio::test]
     async fn read_by_id() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let new_user = mock::user().save().await?;
         let user = User::read_by_id(&new_user.id).await?;
         assert_eq!(user.username, new_user.username);
@@ -24,6 +27,7 @@ mod read {
 
     #[tokio::test]
     async fn list() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let users = (0..5).into_iter().map(|_| mock::user()).collect::<Vec<_>>();
         User::bulk_insert(&users).await?;
         let users = User::list(Default::default(), Default::default()).await?;
@@ -33,6 +37,7 @@ mod read {
 
     #[tokio::test]
     async fn pagination() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let users = (0..10)
             .into_iter()
             .map(|_| mock::user())
@@ -58,6 +63,7 @@ mod read {
 
     #[tokio::test]
     async fn in_operator() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let users = (0..5).into_iter().map(|_| mock::user()).collect::<Vec<_>>();
         User::bulk_insert(&users).await?;
         let users = User::list(
@@ -84,8 +90,98 @@ mod read {
         Ok(())
     }
 
+    #[derive(Debug, Deserialize)]
+    struct PublicUser {
+        #[serde(rename = "_id")]
+        id: String,
+        username: String,
+        password: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn read_as() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let new_user = mock::user().save().await?;
+        let user =
+            User::read_as::<PublicUser>(doc! { "_id": &new_user.id }, doc! { "username": 1 })
+                .await?;
+        assert_eq!(user.id, new_user.id);
+        assert_eq!(user.username, new_user.username);
+        assert!(user.password.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_as() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let users = (0..3).map(|_| mock::user()).collect::<Vec<_>>();
+        User::bulk_insert(&users).await?;
+        let ids = users
+            .iter()
+            .map(|user| user.id.to_string())
+            .collect::<Vec<_>>();
+        let found = User::list_as::<PublicUser>(
+            doc! { "_id": { "$in": &ids } },
+            ListOptions {
+                projection: Some(doc! { "username": 1 }),
+                ..Default::default()
+            },
+        )
+        .await?;
+        assert_eq!(found.len(), 3);
+        assert!(found.iter().all(|user| user.password.is_none()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stream() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let users = (0..5).map(|_| mock::user()).collect::<Vec<_>>();
+        User::bulk_insert(&users).await?;
+        let ids = users
+            .iter()
+            .map(|user| user.id.to_string())
+            .collect::<Vec<_>>();
+        let mut stream = User::stream(
+            doc! { "_id": { "$in": &ids } },
+            ListOptions {
+                batch_size: Some(2),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let mut streamed = 0;
+        while let Some(user) = stream.next().await {
+            assert!(ids.contains(&user?.id));
+            streamed += 1;
+        }
+        assert_eq!(streamed, 5);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn aggregate_stream() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let user = mock::user().save().await?;
+        Post::bulk_insert(
+            &(0..3)
+                .map(|_| mock::post(user.id.to_string()))
+                .collect::<Vec<_>>(),
+        )
+        .await?;
+        let pipeline = vec![doc! { "$match": { "user": &user.id } }];
+        let options = AggregateOptions::builder().batch_size(1).build();
+        let posts = Post::aggregate_stream::<Post>(pipeline, options)
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(posts.len(), 3);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn count() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user_one = mock::user().save().await?;
         let user_two = mock::user().save().await?;
         let count = User::count(Some(doc! { "$or": [
@@ -99,6 +195,7 @@ mod read {
 
     #[tokio::test]
     async fn match_aggregate() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user = mock::user().save().await?;
         let posts = (0..10)
             .into_iter()
@@ -129,6 +226,7 @@ mod read {
 
     #[tokio::test]
     async fn aggregate_arbitrary() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user = mock::user().save().await?;
         Post::bulk_insert(
             &(0..10)
@@ -176,6 +274,7 @@ mod read {
 
     #[tokio::test]
     async fn join_to_many() -> Result<(), MongooseError> {
+        mock::connect().await?;
         #[derive(Debug, Deserialize, Serialize, Clone)]
         struct ShallowPost {
             content: String,
@@ -231,6 +330,7 @@ mod read {
 
     #[tokio::test]
     async fn raw_aggregate() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user = mock::user().save().await?;
         let pipeline = vec![doc! {
             "$match": {
@@ -242,4 +342,62 @@ mod read {
         assert!(found.first().unwrap().id == user.id);
         Ok(())
     }
+
+    // kept out of `users`, a malformed document there would break every other `User::list`
+    #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+    struct Profile {
+        #[serde(rename = "_id")]
+        id: String,
+        username: String,
+        age: u32,
+    }
+
+    impl Model for Profile {
+        const COLLECTION_NAME: Option<&'static str> = Some("lenient_profiles");
+    }
+
+    #[tokio::test]
+    async fn list_lenient() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let profile = Profile {
+            id: mock::nanoid(),
+            username: mock::nanoid(),
+            age: mock::number(),
+        }
+        .save()
+        .await?;
+        let broken_id = mock::nanoid();
+        let collection = Profile::collection()
+            .await?
+            .clone_with_type::<bson::Document>();
+        collection
+            .insert_one(
+                doc! { "_id": &broken_id, "username": &profile.username, "age": "not a number" },
+                None,
+            )
+            .await
+            .map_err(MongooseError::list)?;
+
+        let filter = doc! { "username": &profile.username };
+        let list = Profile::list(filter.clone(), ListOptions::default()).await;
+        let lenient = Profile::list_lenient(filter.clone(), ListOptions::default()).await;
+        let pipeline = vec![doc! { "$match": &filter }];
+        let aggregated = Profile::aggregate_lenient::<Profile>(pipeline, None).await;
+        // removed before asserting so a failure does not leave it behind
+        collection
+            .delete_one(doc! { "_id": &broken_id }, None)
+            .await
+            .map_err(MongooseError::list)?;
+
+        assert!(list.is_err());
+        let list = lenient?;
+        assert_eq!(list.items.len(), 1);
+        assert_eq!(list.items[0].id, profile.id);
+        assert_eq!(list.failures.len(), 1);
+        assert_eq!(list.failures[0].0, bson::Bson::String(broken_id));
+        let aggregated = aggregated?;
+        assert_eq!(aggregated.items.len(), 1);
+        assert_eq!(aggregated.failures.len(), 1);
+        Ok(())
+    }
 }
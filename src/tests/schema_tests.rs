@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod schema {
+    use crate::schema::BsonSchema;
+    use crate::tests::mock;
+    use crate::types::MongooseError;
+    use crate::{
+        doc, DateTime, Document, Model, TimestampSerializer, ValidationAction, ValidationLevel,
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default, BsonSchema)]
+    struct Address {
+        city: String,
+        apt_number: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, BsonSchema, Model)]
+    #[model(timestamps, id = "nanoid")]
+    struct Customer {
+        #[serde(rename = "_id")]
+        id: String,
+        age: u32,
+        score: f64,
+        address: Address,
+        example_array: Vec<u32>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(skip)]
+        cache: Option<String>,
+        created_at: DateTime,
+        updated_at: DateTime,
+    }
+
+    #[test]
+    fn generated_schema() {
+        assert_eq!(
+            Customer::bson_schema(),
+            doc! {
+                "bsonType": "object",
+                "required": ["_id", "age", "score", "address", "example_array", "created_at", "updated_at"],
+                "properties": {
+                    "_id": { "bsonType": "string" },
+                    "age": { "bsonType": ["int", "long"] },
+                    "score": { "bsonType": ["double", "int", "long"] },
+                    "address": {
+                        "bsonType": "object",
+                        "required": ["city"],
+                        "properties": {
+                            "city": { "bsonType": "string" },
+                            "apt_number": { "bsonType": ["string", "null"] },
+                        },
+                    },
+                    "example_array": {
+                        "bsonType": "array",
+                        "items": { "bsonType": ["int", "long"] },
+                    },
+                    "tags": { "bsonType": "array", "items": { "bsonType": "string" } },
+                    "created_at": { "bsonType": "date" },
+                    "updated_at": { "bsonType": "date" },
+                },
+            }
+        );
+        assert!(!Option::<Address>::required());
+        assert!(Customer::default().cache.is_none());
+        assert_eq!(
+            Option::<Vec<i32>>::bson_schema(),
+            doc! { "bsonType": ["array", "null"], "items": { "bsonType": ["int", "long"] } }
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, BsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    struct Shipment {
+        tracking_number: String,
+        #[serde(rename(serialize = "eta"))]
+        arrives_at: DateTime,
+    }
+
+    #[test]
+    fn serde_renames() {
+        assert_eq!(
+            Shipment::bson_schema(),
+            doc! {
+                "bsonType": "object",
+                "required": ["trackingNumber", "eta"],
+                "properties": {
+                    "trackingNumber": { "bsonType": "string" },
+                    "eta": { "bsonType": "date" },
+                },
+            }
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone, BsonSchema)]
+    struct Event {
+        #[serde(with = "TimestampSerializer")]
+        #[bson_schema(bson_type = "date")]
+        starts_at: chrono::DateTime<chrono::Utc>,
+        // stored as written, never widened to a `long`
+        #[bson_schema(bson_type = "int")]
+        attendees: Option<i32>,
+    }
+
+    #[test]
+    fn bson_type_overrides() {
+        assert_eq!(
+            Event::bson_schema(),
+            doc! {
+                "bsonType": "object",
+                "required": ["starts_at"],
+                "properties": {
+                    "starts_at": { "bsonType": "date" },
+                    "attendees": { "bsonType": ["int", "null"] },
+                },
+            }
+        );
+        assert_eq!(
+            chrono::DateTime::<chrono::Utc>::bson_schema(),
+            doc! { "bsonType": "date" }
+        );
+    }
+
+    #[tokio::test]
+    async fn apply_schema_validator() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        // twice, first creating the collection then updating it in place
+        Customer::apply_schema_validator(ValidationLevel::Strict, ValidationAction::Error).await?;
+        Customer::apply_schema_validator(ValidationLevel::Strict, ValidationAction::Error).await?;
+        Customer::default().save().await?;
+        let invalid = Customer::collection()
+            .await?
+            .clone_with_type::<Document>()
+            .insert_one(doc! { "_id": mock::nanoid(), "age": "old" }, None)
+            .await;
+        assert!(invalid.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+#[cfg(test)]
+mod tenant {
+    use crate::change_stream::ChangeEvent;
+    use crate::tenant::{self, Tenancy};
+    use crate::tests::mock;
+    use crate::types::MongooseError;
+    use crate::{doc, ChangeStreamOptions, DateTime, FullDocumentType, Model};
+    use futures::StreamExt;
+    use mongodb::options::FullDocumentBeforeChangeType;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    struct Invoice {
+        #[serde(rename = "_id")]
+        id: String,
+        amount: u32,
+        created_at: DateTime,
+        updated_at: DateTime,
+    }
+
+    impl Default for Invoice {
+        fn default() -> Self {
+            Self {
+                id: Self::generate_nanoid(),
+                amount: u32::default(),
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            }
+        }
+    }
+
+    impl Model for Invoice {
+        fn tenancy() -> Tenancy {
+            Tenancy::Database
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Clone)]
+    struct Ticket {
+        #[serde(rename = "_id")]
+        id: String,
+        tenant_id: String,
+        subject: String,
+        created_at: DateTime,
+        updated_at: DateTime,
+    }
+
+    impl Default for Ticket {
+        fn default() -> Self {
+            Self {
+                id: Self::generate_nanoid(),
+                tenant_id: String::new(),
+                subject: String::new(),
+                created_at: DateTime::now(),
+                updated_at: DateTime::now(),
+            }
+        }
+    }
+
+    impl Model for Ticket {
+        fn tenancy() -> Tenancy {
+            Tenancy::Field("tenant_id")
+        }
+    }
+
+    #[tokio::test]
+    async fn scoped_filter() -> Result<(), MongooseError> {
+        let filter = tenant::scope("a", async {
+            tenant::filter::<Ticket>(doc! { "subject": "x" })
+        })
+        .await?;
+        assert_eq!(filter, doc! { "subject": "x", "tenant_id": "a" });
+        // a caller supplied tenant can only narrow the scope
+        let filter = tenant::scope("a", async {
+            tenant::filter::<Ticket>(doc! { "tenant_id": "b" })
+        })
+        .await?;
+        assert_eq!(
+            filter,
+            doc! { "$and": [{ "tenant_id": "b" }, { "tenant_id": "a" }] }
+        );
+        let unscoped = tenant::filter::<Ticket>(doc! {});
+        assert!(matches!(unscoped, Err(MongooseError::Tenant(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn scoped_pipeline() -> Result<(), MongooseError> {
+        let near = doc! { "$geoNear": { "near": [0, 0], "distanceField": "distance" } };
+        let pipeline = tenant::scope("a", async {
+            tenant::pipeline::<Ticket>(vec![near.clone(), doc! { "$limit": 1 }])
+        })
+        .await?;
+        assert_eq!(
+            pipeline,
+            vec![
+                near,
+                doc! { "$match": { "tenant_id": "a" } },
+                doc! { "$limit": 1 }
+            ]
+        );
+        let pipeline = tenant::scope("a", async {
+            tenant::pipeline::<Ticket>(vec![doc! { "$limit": 1 }])
+        })
+        .await?;
+        assert_eq!(pipeline[0], doc! { "$match": { "tenant_id": "a" } });
+        let stats = tenant::scope("a", async {
+            tenant::pipeline::<Ticket>(vec![doc! { "$collStats": {} }])
+        })
+        .await;
+        assert!(matches!(stats, Err(MongooseError::Tenant(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn scoped_change_stream() -> Result<(), MongooseError> {
+        let (pipeline, options) =
+            tenant::scope("a", async { tenant::change_stream::<Ticket>(vec![], None) }).await?;
+        assert_eq!(
+            pipeline,
+            vec![doc! { "$match": { "fullDocument.tenant_id": "a" } }]
+        );
+        // updates would be dropped without the looked up document
+        let options = options.unwrap();
+        assert!(matches!(
+            options.full_document,
+            Some(FullDocumentType::UpdateLookup)
+        ));
+        // deletes are matched on their pre-image when one is requested
+        let options = ChangeStreamOptions::builder()
+            .full_document(Some(FullDocumentType::Required))
+            .full_document_before_change(Some(FullDocumentBeforeChangeType::Required))
+            .build();
+        let (pipeline, options) = tenant::scope("a", async {
+            tenant::change_stream::<Ticket>(vec![], Some(options))
+        })
+        .await?;
+        assert_eq!(
+            pipeline,
+            vec![doc! {
+                "$match": {
+                    "$or": [
+                        { "fullDocument.tenant_id": "a" },
+                        { "fullDocumentBeforeChange.tenant_id": "a" },
+                    ]
+                }
+            }]
+        );
+        assert!(matches!(
+            options.unwrap().full_document,
+            Some(FullDocumentType::Required)
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn watch_per_tenant() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let tenant_a = mock::nanoid();
+        let tenant_b = mock::nanoid();
+        let mut stream = tenant::scope(&tenant_a, Ticket::watch(vec![], None)).await?;
+        tenant::scope(&tenant_b, Ticket::default().save()).await?;
+        let ticket = tenant::scope(&tenant_a, Ticket::default().save()).await?;
+        tenant::scope(
+            &tenant_a,
+            Ticket::update(doc! { "_id": &ticket.id }, doc! { "subject": "updated" }),
+        )
+        .await?;
+        let Some(Ok(ChangeEvent::Insert { document, .. })) = stream.next().await else {
+            panic!("expected an insert event");
+        };
+        assert_eq!(document.id, ticket.id);
+        let Some(Ok(ChangeEvent::Update { document, .. })) = stream.next().await else {
+            panic!("expected an update event");
+        };
+        assert_eq!(
+            document.map(|ticket| ticket.subject).as_deref(),
+            Some("updated")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tenant_field_is_read_only() {
+        for updates in [
+            doc! { "$set": { "tenant_id": "b" } },
+            doc! { "$unset": { "tenant_id": "" } },
+            doc! { "$rename": { "subject": "tenant_id" } },
+            doc! { "$rename": { "tenant_id": "owner" } },
+        ] {
+            let result = tenant::updates::<Ticket>(updates);
+            assert!(matches!(result, Err(MongooseError::Tenant(_))));
+        }
+        assert!(tenant::updates::<Ticket>(doc! { "$set": { "subject": "x" } }).is_ok());
+        for stage in [
+            doc! { "$set": { "tenant_id": "b" } },
+            doc! { "$unset": ["subject", "tenant_id"] },
+            doc! { "$replaceWith": { "subject": "x" } },
+        ] {
+            let result = tenant::update_pipeline::<Ticket>(vec![stage]);
+            assert!(matches!(result, Err(MongooseError::Tenant(_))));
+        }
+        assert!(
+            tenant::update_pipeline::<Ticket>(vec![doc! { "$set": { "subject": "x" } }]).is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn row_per_tenant() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let tenant_a = mock::nanoid();
+        let tenant_b = mock::nanoid();
+        let ticket = tenant::scope(&tenant_a, async {
+            Ticket::bulk_insert(&[Ticket::default(), Ticket::default()]).await?;
+            Ticket {
+                subject: mock::nanoid(),
+                ..Default::default()
+            }
+            .save()
+            .await
+        })
+        .await?;
+        assert_eq!(ticket.tenant_id, tenant_a);
+        let count = tenant::scope(&tenant_a, Ticket::count(None)).await?;
+        assert_eq!(count, 3);
+        let pipeline = vec![doc! { "$match": { "subject": &ticket.subject } }];
+        let leaked = tenant::scope(&tenant_b, Ticket::aggregate::<Ticket>(pipeline, None)).await?;
+        assert!(leaked.is_empty());
+        let leaked = tenant::scope(&tenant_b, Ticket::read_by_id(&ticket.id)).await;
+        assert!(leaked.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn requires_scope() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let result = Invoice::default().save().await;
+        assert!(matches!(result, Err(MongooseError::Tenant(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn tenant_database_names() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        for name in ["admin", "config", "local", "", "a.b", "a b", "a/b", "a$b"] {
+            assert!(tenant::validate_database_name(name).is_err(), "{name:?}");
+        }
+        assert!(tenant::validate_database_name(&"a".repeat(64)).is_err());
+        assert!(tenant::validate_database_name("tenant_acme-01").is_ok());
+        let database = tenant::scope("acme.prod", Invoice::database()).await;
+        assert!(matches!(database, Err(MongooseError::Tenant(_))));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn database_per_tenant() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let invoice = tenant::scope("mongoose-rs-tenant-a", async {
+            assert_eq!(
+                Invoice::database().await?.name(),
+                "tenant_mongoose-rs-tenant-a"
+            );
+            Invoice {
+                amount: mock::number(),
+                ..Default::default()
+            }
+            .save()
+            .await
+        })
+        .await?;
+        let found = tenant::scope("mongoose-rs-tenant-a", Invoice::read_by_id(&invoice.id)).await?;
+        assert_eq!(found.amount, invoice.amount);
+        let missing = tenant::scope(
+            "mongoose-rs-tenant-b",
+            Invoice::read(doc! { "_id": &invoice.id }),
+        )
+        .await;
+        assert!(missing.is_err());
+        Ok(())
+    }
+}
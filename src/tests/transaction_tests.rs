@@ -0,0 +1,51 @@
+#[cfg(test)]
+mod transaction {
+    use crate::tests::mock::{self, Post, User};
+    use crate::types::MongooseError;
+    use crate::{doc, Model};
+
+    #[tokio::test]
+    async fn commit() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let user = mock::user();
+        let (user, post) = crate::transaction(|session| {
+            let user = user.clone();
+            Box::pin(async move {
+                let user = user.save_with_session(session).await?;
+                let post = mock::post(user.id.clone())
+                    .save_with_session(session)
+                    .await?;
+                Ok((user, post))
+            })
+        })
+        .await?;
+        let found = Post::read(doc! { "user": &user.id }).await?;
+        assert_eq!(found.id, post.id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn abort() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let user = mock::user();
+        let result = crate::transaction(|session| {
+            let user = user.clone();
+            Box::pin(async move {
+                user.save_with_session(session).await?;
+                let found = User::read_with_session(doc! { "_id": &user.id }, session).await?;
+                assert_eq!(found.id, user.id);
+                Err::<(), _>(MongooseError::NotFound("rollback".into()))
+            })
+        })
+        .await;
+        // the closure's own error comes back, not a server or commit failure
+        match result {
+            Err(MongooseError::NotFound(source)) => assert_eq!(source.message(), "rollback"),
+            other => panic!("expected the rollback error, got {other:?}"),
+        }
+        // insert should have been rolled back
+        let found = User::read_by_id(&user.id).await;
+        assert!(matches!(found, Err(MongooseError::NotFound(source)) if source.driver().is_none()));
+        Ok(())
+    }
+}
@@ -2,10 +2,171 @@
 mod update {
     use crate::tests::mock::{self, User};
     use crate::types::MongooseError;
-    use crate::{doc, Model};
+    use crate::{doc, Model, Update};
+    use bson::Bson;
+    use std::collections::HashMap;
+
+    #[test]
+    fn normalize_updates() {
+        let updates = User::normalize_updates(&doc! {
+            "username": "jude",
+            "$set": { "age": 30 },
+            "$inc": { "address.address": 1 },
+            "$unset": { "slug": "" },
+            "$rename": { "avatar_hash": "avatar" },
+            "$addToSet": { "example_array": 7 },
+        })
+        .unwrap();
+        let set = updates.get_document("$set").unwrap();
+        assert_eq!(set.get_str("username").unwrap(), "jude");
+        assert_eq!(set.get_i32("age").unwrap(), 30);
+        assert!(set.contains_key("updated_at"));
+        for operator in ["$inc", "$unset", "$rename", "$addToSet"] {
+            assert!(updates.contains_key(operator));
+        }
+
+        // the caller's own timestamp wins
+        let updates =
+            User::normalize_updates(&doc! { "$currentDate": { "updated_at": true } }).unwrap();
+        assert!(!updates.contains_key("$set"));
+
+        for conflicting in [
+            doc! { "age": 1, "$inc": { "age": 1 } },
+            doc! { "address": {}, "$set": { "address.city": "x" } },
+            doc! { "$unset": { "address.city": "" }, "$min": { "address": {} } },
+            doc! { "$rename": { "slug": "username" }, "username": "x" },
+        ] {
+            let err = User::normalize_updates(&conflicting).unwrap_err();
+            assert!(
+                matches!(err, MongooseError::InvalidUpdate(_)),
+                "{conflicting}"
+            );
+        }
+        // sibling paths sharing a prefix are fine
+        assert!(User::normalize_updates(
+            &doc! { "address.city": "x", "$inc": { "address.cityCode": 1 } }
+        )
+        .is_ok());
+        assert!(User::normalize_updates(&doc! { "$inc": 1 }).is_err());
+        // `$bit` takes part in conflict checks, unknown operators are left to the server
+        assert!(
+            User::normalize_updates(&doc! { "age": 1, "$bit": { "age": { "and": 1 } } }).is_err()
+        );
+        let updates = User::normalize_updates(&doc! { "$future": { "age": 1 } }).unwrap();
+        assert_eq!(updates.get_document("$future").unwrap(), &doc! { "age": 1 });
+    }
+
+    #[tokio::test]
+    async fn set_operator() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let user = mock::user().save().await?;
+        let updated = User::update(
+            doc! { "_id": &user.id },
+            doc! {
+                "$set": { "age": 42 },
+                "address.city": "Elsewhere",
+                "$max": { "address.address": 1_000_000 },
+                "$mul": { "example_array.0": 0 },
+            },
+        )
+        .await?;
+        assert_eq!(updated.age, 42);
+        assert_eq!(updated.address.city, "Elsewhere");
+        assert_eq!(updated.address.address, 1_000_000);
+        assert_eq!(updated.example_array[0], 0);
+        assert!(updated.updated_at > user.updated_at);
+        Ok(())
+    }
+
+    #[test]
+    fn update_builder() {
+        let updates = Update::new()
+            .set("address.city", "Denver")
+            .set("address.apt_number", None::<String>)
+            .inc("age", 1)
+            .push_each(
+                "example_array",
+                [3_u32, 1, 2],
+                Some(-5),
+                Some(Bson::Int32(1)),
+            )
+            .pull("tags", doc! { "$in": ["a", "b"] })
+            .add_to_set("roles", "admin")
+            .unset("slug")
+            .rename("avatar_hash", "avatar")
+            .min("score", 0.5)
+            .max("address.address", 10)
+            .build()
+            .unwrap();
+        assert_eq!(
+            updates,
+            doc! {
+                "$set": { "address.city": "Denver", "address.apt_number": null },
+                "$inc": { "age": 1 },
+                "$push": { "example_array": { "$each": [3_i64, 1_i64, 2_i64], "$slice": -5, "$sort": 1 } },
+                "$pull": { "tags": { "$in": ["a", "b"] } },
+                "$addToSet": { "roles": "admin" },
+                "$unset": { "slug": "" },
+                "$rename": { "avatar_hash": "avatar" },
+                "$min": { "score": 0.5 },
+                "$max": { "address.address": 10 },
+            }
+        );
+        assert!(User::normalize_updates(&updates).is_ok());
+        let conflicting = Update::new().set("age", 1).inc("age", 1).build().unwrap();
+        assert!(User::normalize_updates(&conflicting).is_err());
+        // maps with non string keys cannot be represented in bson
+        let invalid = Update::new().set("lookup", HashMap::from([(1, 2)])).build();
+        assert!(matches!(invalid, Err(MongooseError::InvalidUpdate(_))));
+    }
+
+    #[tokio::test]
+    async fn update_with_builder() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let user = mock::user().save().await?;
+        let updates = Update::new()
+            .set("address.city", "Denver")
+            .inc("age", 1)
+            .push_each("example_array", [5_u32, 4], Some(-4), None)
+            .build()?;
+        let updated = User::update(doc! { "_id": &user.id }, updates).await?;
+        assert_eq!(updated.address.city, "Denver");
+        assert_eq!(updated.age, user.age + 1);
+        assert_eq!(updated.example_array.len(), 4);
+        assert_eq!(updated.example_array[3], 4);
+        Ok(())
+    }
+
+    #[cfg(feature = "timestamps")]
+    #[test]
+    fn pipeline_timestamp() {
+        let stamped = crate::update::pipeline(vec![doc! { "$set": { "slug": "x" } }]);
+        assert_eq!(stamped.len(), 2);
+        // the caller's own timestamp wins, like `normalize_updates`
+        let own = vec![doc! { "$set": { "updated_at": "$created_at" } }];
+        assert_eq!(crate::update::pipeline(own.clone()), own);
+    }
+
+    #[tokio::test]
+    async fn update_pipeline() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let user = mock::user().save().await?;
+        let pipeline = vec![doc! {
+            "$set": { "slug": { "$concat": ["$username", "-", "$address.state"] } }
+        }];
+        assert_eq!(crate::update::pipeline(pipeline.clone()).len(), 2);
+        let updated = User::update_pipeline(doc! { "_id": &user.id }, pipeline.clone()).await?;
+        assert_eq!(updated.slug, format!("{}-CA", user.username));
+        assert!(updated.updated_at > user.updated_at);
+
+        let result = User::bulk_update_pipeline(doc! { "_id": &user.id }, pipeline).await?;
+        assert_eq!(result.matched_count, 1);
+        Ok(())
+    }
 
     #[tokio::test]
     async fn increment() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user = mock::user().save().await?;
         let updated = User::update(
             doc! { "_id": &user.id },
@@ -20,6 +181,7 @@ mod update {
 
     #[tokio::test]
     async fn decrement() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user = mock::user().save().await?;
         let updated = User::update(
             doc! { "_id": &user.id },
@@ -34,6 +196,7 @@ mod update {
 
     #[tokio::test]
     async fn push() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user = mock::user().save().await?;
         assert!(user.example_array.len() == 3);
         let updated = User::update(
@@ -49,6 +212,7 @@ mod update {
 
     #[tokio::test]
     async fn pull() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user = mock::user().save().await?;
         assert!(user.example_array.len() == 3);
         let updated = User::update(
@@ -64,6 +228,7 @@ mod update {
 
     #[tokio::test]
     async fn update_sub_document() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let user = mock::user().save().await?;
         let new_city = mock::nanoid();
         let updated = User::update(
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod validation {
+    use crate::types::MongooseError;
+    use crate::validation::{self, ValidationErrors};
+    use crate::Model;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, Clone, Default)]
+    struct Signup {
+        username: String,
+        email: String,
+        tags: Vec<String>,
+    }
+
+    impl Model for Signup {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::new();
+            validation::length(&mut errors, "username", &self.username, Some(3), None);
+            validation::email(&mut errors, "email", &self.email);
+            validation::length(&mut errors, "tags", &self.tags, None, Some(2));
+            errors.into_result()
+        }
+    }
+
+    #[test]
+    fn validators() {
+        let mut errors = ValidationErrors::new();
+        validation::length(&mut errors, "a", "héllo", Some(5), Some(5));
+        validation::range(&mut errors, "b", &2.5, Some(0.0), Some(5.0));
+        validation::email(&mut errors, "c", "someone@mail.co");
+        validation::regex(&mut errors, "d", "abc_123", "^[a-z0-9_]+$");
+        validation::one_of(&mut errors, "e", &"admin".to_string(), &["admin", "user"]);
+        assert!(errors.is_empty());
+
+        validation::length(&mut errors, "a", "hi", Some(3), Some(5));
+        validation::range(&mut errors, "b", &-1, Some(0), None);
+        for email in [
+            "",
+            "@mail.com",
+            "a@b",
+            "a b@mail.com",
+            "a@mail.com.",
+            "a@@mail.com",
+        ] {
+            validation::email(&mut errors, "c", email);
+        }
+        validation::regex(&mut errors, "d", "ABC", "^[a-z]+$");
+        validation::regex(&mut errors, "d", "abc", "(unclosed");
+        validation::one_of(&mut errors, "e", &3, &[1, 2]);
+        assert_eq!(
+            errors.field("a")[0].message,
+            "length must be between 3 and 5"
+        );
+        assert_eq!(errors.field("b")[0].message, "must be at least 0");
+        assert_eq!(errors.field("c").len(), 6);
+        assert_eq!(errors.field("d").len(), 2);
+        assert_eq!(errors.field("e")[0].code, "one_of");
+    }
+
+    #[test]
+    fn nested_paths() {
+        let mut nested = ValidationErrors::new();
+        nested.add("zip", "regex", "must match");
+        let mut errors = ValidationErrors::new();
+        errors.nest("address", Err(nested));
+        errors.nest("other", Ok(()));
+        assert_eq!(errors.errors.len(), 1);
+        assert_eq!(errors.errors[0].path, "address.zip");
+        assert_eq!(errors.to_string(), "address.zip: must match");
+    }
+
+    #[tokio::test]
+    async fn save_rejects_invalid() {
+        let signup = Signup {
+            username: "ab".to_string(),
+            email: "nope".to_string(),
+            tags: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        };
+        let Err(MongooseError::Validation(errors)) = signup.save().await else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(errors.errors.len(), 3);
+        let valid = Signup {
+            username: "abc".to_string(),
+            email: "someone@mail.com".to_string(),
+            tags: vec![],
+        };
+        // errors are reported under the position of the failing document
+        let Err(MongooseError::Validation(errors)) = Signup::bulk_insert(&[valid, signup]).await
+        else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(errors.errors.len(), 3);
+        assert_eq!(errors.field("1.email")[0].code, "email");
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod views {
-    use crate::tests::mock::{Address, Post, User};
+    use crate::tests::mock::{self, Address, Post, User};
     use crate::types::MongooseError;
     use crate::{doc, DateTime, IndexModel, Model};
     use serde::{Deserialize, Serialize};
@@ -36,6 +36,7 @@ mod views {
 
     #[tokio::test]
     async fn create_view() -> Result<(), MongooseError> {
+        mock::connect().await?;
         // create index on post for joining user
         // ix should be used for aggregation pipeline in view
         let indexes = &[IndexModel::builder().keys(doc! { "user": 1 }).build()];
@@ -63,6 +64,7 @@ mod views {
     #[ignore = "should wait for view to be created"]
     #[tokio::test]
     async fn read_from_view() -> Result<(), MongooseError> {
+        mock::connect().await?;
         let post = UserPosts::read(doc! {}).await;
         assert!(post.is_ok());
         Ok(())
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod watch {
+    use crate::change_stream::ChangeEvent;
+    use crate::tests::mock::{self, User};
+    use crate::types::MongooseError;
+    use crate::{doc, ChangeStreamOptions, Model};
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn insert_then_resume() -> Result<(), MongooseError> {
+        mock::connect().await?;
+        let user = mock::user();
+        let pipeline = vec![doc! { "$match": { "documentKey._id": &user.id } }];
+        let mut stream = User::watch(pipeline.clone(), None).await?;
+        user.save().await?;
+        let Some(Ok(ChangeEvent::Insert { document, .. })) = stream.next().await else {
+            panic!("expected an insert event");
+        };
+        assert_eq!(document.username, user.username);
+        // resume as if restarted after persisting the token
+        let token = stream.resume_token();
+        drop(stream);
+        User::update(doc! { "_id": &user.id }, doc! { "age": 1 }).await?;
+        let options = ChangeStreamOptions::builder().resume_after(token).build();
+        let mut stream = User::watch(pipeline, options).await?;
+        let Some(Ok(ChangeEvent::Update { updated_fields, .. })) = stream.next().await else {
+            panic!("expected an update event");
+        };
+        assert!(updated_fields.contains_key("age"));
+        Ok(())
+    }
+}
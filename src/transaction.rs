@@ -0,0 +1,79 @@
+use crate::{connection, types::MongooseError};
+use futures::future::BoxFuture;
+use mongodb::{
+    error::{TRANSIENT_TRANSACTION_ERROR, UNKNOWN_TRANSACTION_COMMIT_RESULT},
+    ClientSession,
+};
+use std::time::{Duration, Instant};
+
+// same budget the driver's own `with_transaction` helper uses
+const RETRY_TIMEOUT: Duration = Duration::from_secs(120);
+
+// runs `callback` inside a transaction on the default connection
+//
+// mongoose::transaction(|session| {
+//     Box::pin(async move {
+//         let user = user.save_with_session(session).await?;
+//         post.save_with_session(session).await
+//     })
+// })
+pub async fn transaction<T, F>(callback: F) -> Result<T, MongooseError>
+where
+    F: for<'a> FnMut(&'a mut ClientSession) -> BoxFuture<'a, Result<T, MongooseError>>,
+{
+    transaction_on(connection::DEFAULT_CONNECTION, callback).await
+}
+
+// `callback` may run more than once, transient errors restart the whole transaction
+pub async fn transaction_on<T, F>(
+    connection_name: &str,
+    mut callback: F,
+) -> Result<T, MongooseError>
+where
+    F: for<'a> FnMut(&'a mut ClientSession) -> BoxFuture<'a, Result<T, MongooseError>>,
+{
+    let client = connection::get_named(connection_name)?.client;
+    let mut session = client
+        .start_session(None)
+        .await
+        .map_err(MongooseError::transaction)?;
+    let started = Instant::now();
+    'transaction: loop {
+        session
+            .start_transaction(None)
+            .await
+            .map_err(MongooseError::transaction)?;
+        let value = match callback(&mut session).await {
+            Ok(value) => value,
+            Err(err) => {
+                if let Err(abort_err) = session.abort_transaction().await {
+                    tracing::warn!("error aborting transaction: {:?}", abort_err);
+                }
+                // errors mapped outside of `in_session` still carry the driver's label
+                if (matches!(err, MongooseError::TransientTransaction(_))
+                    || err.contains_label(TRANSIENT_TRANSACTION_ERROR))
+                    && started.elapsed() < RETRY_TIMEOUT
+                {
+                    continue 'transaction;
+                }
+                return Err(err);
+            }
+        };
+        loop {
+            match session.commit_transaction().await {
+                Ok(()) => return Ok(value),
+                Err(err) if started.elapsed() >= RETRY_TIMEOUT => {
+                    return Err(MongooseError::transaction(err))
+                }
+                Err(err) if err.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT) => {
+                    tracing::warn!("retrying transaction commit: {:?}", err);
+                }
+                Err(err) if err.contains_label(TRANSIENT_TRANSACTION_ERROR) => {
+                    tracing::warn!("retrying transaction: {:?}", err);
+                    continue 'transaction;
+                }
+                Err(err) => return Err(MongooseError::transaction(err)),
+            }
+        }
+    }
+}
@@ -1,14 +1,26 @@
-use bson::Document;
-use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
-use thiserror::Error;
+use crate::validation::ValidationErrors;
+use bson::{Bson, Document};
+use mongodb::{
+    error::{
+        ErrorKind, WriteFailure, RETRYABLE_WRITE_ERROR, TRANSIENT_TRANSACTION_ERROR,
+        UNKNOWN_TRANSACTION_COMMIT_RESULT,
+    },
+    options::FindOptions,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{fmt::Debug, sync::Arc};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ListOptions {
+    // 0 for no limit, defaults to 1000
     pub limit: i64,
     pub skip: u64,
     pub sort: Document,
     pub allow_disk_use: bool,
+    // documents fetched per round trip, the server default when `None`
+    pub batch_size: Option<u32>,
+    // fields to include or exclude, use with `list_as` when excluding required fields
+    pub projection: Option<Document>,
 }
 
 impl Default for ListOptions {
@@ -18,79 +30,483 @@ impl Default for ListOptions {
             skip: 0,
             sort: Document::default(),
             allow_disk_use: false,
+            batch_size: None,
+            projection: None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Error)]
+// results of `list_lenient` / `aggregate_lenient`
+// documents that failed to deserialize are kept as `(_id, error)` instead of failing the call
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LenientList<T> {
+    pub items: Vec<T>,
+    pub failures: Vec<(Bson, MongooseError)>,
+}
+
+impl<T> Default for LenientList<T> {
+    fn default() -> Self {
+        Self {
+            items: vec![],
+            failures: vec![],
+        }
+    }
+}
+
+impl<T: DeserializeOwned> LenientList<T> {
+    pub(crate) fn push(
+        &mut self,
+        document: Document,
+        map: impl FnOnce(bson::de::Error) -> MongooseError,
+    ) {
+        let id = document.get("_id").cloned().unwrap_or(Bson::Null);
+        match bson::from_document(document) {
+            Ok(item) => self.items.push(item),
+            Err(err) => self.failures.push((id, map(err))),
+        }
+    }
+}
+
+impl From<ListOptions> for FindOptions {
+    fn from(options: ListOptions) -> Self {
+        Self::builder()
+            .skip(options.skip)
+            .limit(options.limit)
+            .sort(options.sort)
+            .allow_disk_use(options.allow_disk_use)
+            .batch_size(options.batch_size)
+            .projection(options.projection)
+            .build()
+    }
+}
+
+// variants built from another error only describe the operation, the error itself is the
+// `source`, so reporters walking the chain print its message once; see `error_source()`
+#[derive(Serialize, Deserialize, Debug)]
 pub enum MongooseError {
-    #[error("no document found: {0}")]
-    NotFound(String),
-    #[error("error inserting document: {0}")]
-    InsertOne(String),
-    #[error("error bulk inserting documents: {0}")]
-    BulkInsert(String),
-    #[error("error listing documents: {0}")]
-    List(String),
-    #[error("error updating document: {0}")]
-    Update(String),
-    #[error("error bulk updating documents: {0}")]
-    BulkUpdate(String),
-    #[error("error deleting document: {0}")]
-    Delete(String),
-    #[error("error bulk deleting documents: {0}")]
-    BulkDelete(String),
-    #[error("error counting documents: {0}")]
-    Count(String),
-    #[error("error aggregating documents: {0}")]
-    Aggregate(String),
-    #[error("error creating indexes: {0}")]
-    CreateIndex(String),
+    NotFound(ErrorSource),
+    InsertOne(ErrorSource),
+    BulkInsert(ErrorSource),
+    List(ErrorSource),
+    Update(ErrorSource),
+    BulkUpdate(ErrorSource),
+    Delete(ErrorSource),
+    BulkDelete(ErrorSource),
+    Count(ErrorSource),
+    Aggregate(ErrorSource),
+    CreateIndex(ErrorSource),
+    SyncIndexes(ErrorSource),
+    Schema(ErrorSource),
+    Connection(ErrorSource),
+    Tenant(String),
+    Transaction(ErrorSource),
+    TransientTransaction(ErrorSource),
+    Watch(ErrorSource),
+    Pagination(ErrorSource),
+    Migration(ErrorSource),
+    Hook(String),
+    InvalidUpdate(String),
+    InvalidQuery(String),
+    Validation(ValidationErrors),
+    // a document that does not match the shape of the type it is read into
+    Deserialize(ErrorSource),
 }
 
 impl MongooseError {
-    pub fn not_found(error: impl std::error::Error) -> Self {
+    // bson decoding failures get their own kind whatever the operation
+    fn from_error(
+        error: impl std::error::Error + Send + Sync + 'static,
+        kind: impl FnOnce(ErrorSource) -> Self,
+    ) -> Self {
+        let source = ErrorSource::new(error);
+        if source.is_deserialize() {
+            return Self::Deserialize(source);
+        }
+        kind(source)
+    }
+    pub fn not_found(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR FINDING DOCUMENTS]: {:?}", error);
-        Self::NotFound(error.to_string())
+        Self::from_error(error, Self::NotFound)
     }
-    pub fn insert_one(error: impl std::error::Error) -> Self {
+    pub fn insert_one(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR INSERTING DOCUMENT]: {:?}", error);
-        Self::InsertOne(error.to_string())
+        Self::from_error(error, Self::InsertOne)
     }
-    pub fn bulk_insert(error: impl std::error::Error) -> Self {
+    pub fn bulk_insert(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR BULK INSERTING DOCUMENTS]: {:?}", error);
-        Self::BulkInsert(error.to_string())
+        Self::from_error(error, Self::BulkInsert)
     }
-    pub fn list(error: impl std::error::Error) -> Self {
+    pub fn list(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR LISTING DOCUMENTS]: {:?}", error);
-        Self::List(error.to_string())
+        Self::from_error(error, Self::List)
     }
-    pub fn update(error: impl std::error::Error) -> Self {
+    pub fn update(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR UPDATING DOCUMENT]: {:?}", error);
-        Self::Update(error.to_string())
+        Self::from_error(error, Self::Update)
     }
-    pub fn bulk_update(error: impl std::error::Error) -> Self {
+    pub fn bulk_update(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR BULK UPDATING DOCUMENTS]: {:?}", error);
-        Self::BulkUpdate(error.to_string())
+        Self::from_error(error, Self::BulkUpdate)
     }
-    pub fn delete(error: impl std::error::Error) -> Self {
+    pub fn delete(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR DELETING DOCUMENT]: {:?}", error);
-        Self::Delete(error.to_string())
+        Self::from_error(error, Self::Delete)
     }
-    pub fn bulk_delete(error: impl std::error::Error) -> Self {
+    pub fn bulk_delete(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR BULK DELETING DOCUMENTS]: {:?}", error);
-        Self::BulkDelete(error.to_string())
+        Self::from_error(error, Self::BulkDelete)
     }
-    pub fn count(error: impl std::error::Error) -> Self {
+    pub fn count(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR COUNTING DOCUMENTS]: {:?}", error);
-        Self::Count(error.to_string())
+        Self::from_error(error, Self::Count)
     }
-    pub fn aggregate(error: impl std::error::Error) -> Self {
+    pub fn aggregate(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR AGGREGATING DOCUMENTS]: {:?}", error);
-        Self::Aggregate(error.to_string())
+        Self::from_error(error, Self::Aggregate)
     }
-    pub fn create_index(error: impl std::error::Error) -> Self {
+    pub fn create_index(error: impl std::error::Error + Send + Sync + 'static) -> Self {
         tracing::error!("[MONGODB ERROR CREATING INDEX]: {:?}", error);
-        Self::CreateIndex(error.to_string())
+        Self::from_error(error, Self::CreateIndex)
+    }
+    pub fn sync_indexes(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        tracing::error!("[MONGODB ERROR SYNCING INDEXES]: {:?}", error);
+        Self::from_error(error, Self::SyncIndexes)
+    }
+    pub fn schema(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        tracing::error!("[MONGODB ERROR APPLYING SCHEMA]: {:?}", error);
+        Self::from_error(error, Self::Schema)
+    }
+    pub fn transaction(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        tracing::error!("[MONGODB ERROR RUNNING TRANSACTION]: {:?}", error);
+        Self::from_error(error, Self::Transaction)
+    }
+    // keeps the transient label visible so `transaction` can retry
+    pub fn in_session(
+        error: mongodb::error::Error,
+        map: impl FnOnce(mongodb::error::Error) -> Self,
+    ) -> Self {
+        if error.contains_label(TRANSIENT_TRANSACTION_ERROR) {
+            tracing::warn!("[MONGODB TRANSIENT TRANSACTION ERROR]: {:?}", error);
+            return Self::from_error(error, Self::TransientTransaction);
+        }
+        map(error)
+    }
+    pub fn watch(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        tracing::error!("[MONGODB ERROR WATCHING COLLECTION]: {:?}", error);
+        Self::from_error(error, Self::Watch)
+    }
+    pub fn pagination(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        tracing::error!("[MONGOOSE ERROR PAGINATING DOCUMENTS]: {:?}", error);
+        Self::from_error(error, Self::Pagination)
+    }
+    pub fn migration(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        tracing::error!("[MONGODB ERROR RUNNING MIGRATIONS]: {:?}", error);
+        Self::from_error(error, Self::Migration)
+    }
+    pub fn connection(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        tracing::error!("[MONGODB ERROR CONNECTING]: {:?}", error);
+        Self::from_error(error, Self::Connection)
+    }
+    pub fn deserialize(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        tracing::error!("[MONGOOSE ERROR DESERIALIZING DOCUMENT]: {:?}", error);
+        Self::Deserialize(ErrorSource::new(error))
+    }
+
+    // the error this one was built from, `None` for errors raised by mongoose itself
+    pub const fn error_source(&self) -> Option<&ErrorSource> {
+        match self {
+            Self::NotFound(source)
+            | Self::InsertOne(source)
+            | Self::BulkInsert(source)
+            | Self::List(source)
+            | Self::Update(source)
+            | Self::BulkUpdate(source)
+            | Self::Delete(source)
+            | Self::BulkDelete(source)
+            | Self::Count(source)
+            | Self::Aggregate(source)
+            | Self::CreateIndex(source)
+            | Self::SyncIndexes(source)
+            | Self::Schema(source)
+            | Self::Connection(source)
+            | Self::Transaction(source)
+            | Self::TransientTransaction(source)
+            | Self::Watch(source)
+            | Self::Pagination(source)
+            | Self::Migration(source)
+            | Self::Deserialize(source) => Some(source),
+            Self::Tenant(_)
+            | Self::Hook(_)
+            | Self::InvalidUpdate(_)
+            | Self::InvalidQuery(_)
+            | Self::Validation(_) => None,
+        }
+    }
+
+    pub fn driver_error(&self) -> Option<&mongodb::error::Error> {
+        self.error_source()?.driver()
+    }
+
+    pub fn contains_label(&self, label: &str) -> bool {
+        self.driver_error()
+            .is_some_and(|error| error.contains_label(label))
+    }
+
+    // server error codes, including every write error of a bulk write
+    fn codes(&self) -> Vec<i32> {
+        let Some(error) = self.driver_error() else {
+            return vec![];
+        };
+        match error.kind.as_ref() {
+            ErrorKind::Command(command) => vec![command.code],
+            ErrorKind::Write(WriteFailure::WriteError(write)) => vec![write.code],
+            ErrorKind::Write(WriteFailure::WriteConcernError(concern)) => vec![concern.code],
+            ErrorKind::BulkWrite(failure) => failure
+                .write_errors
+                .iter()
+                .flatten()
+                .map(|write| write.code)
+                .chain(
+                    failure
+                        .write_concern_error
+                        .iter()
+                        .map(|concern| concern.code),
+                )
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn messages(&self) -> Vec<&str> {
+        let Some(error) = self.driver_error() else {
+            return vec![];
+        };
+        match error.kind.as_ref() {
+            ErrorKind::Command(command) => vec![command.message.as_str()],
+            ErrorKind::Write(WriteFailure::WriteError(write)) => vec![write.message.as_str()],
+            ErrorKind::BulkWrite(failure) => failure
+                .write_errors
+                .iter()
+                .flatten()
+                .map(|write| write.message.as_str())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    pub fn is_duplicate_key(&self) -> bool {
+        self.codes().contains(&DUPLICATE_KEY)
+    }
+
+    // the index and key values of the first duplicate key error
+    pub fn duplicate_key(&self) -> Option<DuplicateKey> {
+        if !self.is_duplicate_key() {
+            return None;
+        }
+        self.messages().into_iter().find_map(DuplicateKey::parse)
+    }
+
+    pub fn is_write_conflict(&self) -> bool {
+        self.codes().contains(&WRITE_CONFLICT)
+    }
+
+    // network timeouts, server selection timeouts and operations exceeding their time limit
+    pub fn is_timeout(&self) -> bool {
+        let Some(error) = self.driver_error() else {
+            return false;
+        };
+        match error.kind.as_ref() {
+            ErrorKind::Io(io) => io.kind() == std::io::ErrorKind::TimedOut,
+            ErrorKind::ServerSelection { .. } => true,
+            _ => self.codes().iter().any(|code| TIMEOUT_CODES.contains(code)),
+        }
+    }
+
+    // whether running the operation again may succeed
+    pub fn is_retryable(&self) -> bool {
+        if matches!(self, Self::TransientTransaction(_)) {
+            return true;
+        }
+        let Some(error) = self.driver_error() else {
+            return false;
+        };
+        [
+            RETRYABLE_WRITE_ERROR,
+            TRANSIENT_TRANSACTION_ERROR,
+            UNKNOWN_TRANSACTION_COMMIT_RESULT,
+        ]
+        .iter()
+        .any(|label| error.contains_label(label))
+            || matches!(
+                error.kind.as_ref(),
+                ErrorKind::Io(_)
+                    | ErrorKind::ConnectionPoolCleared { .. }
+                    | ErrorKind::ServerSelection { .. }
+            )
+            || self
+                .codes()
+                .iter()
+                .any(|code| RETRYABLE_CODES.contains(code))
+    }
+
+    pub const fn is_deserialize(&self) -> bool {
+        matches!(self, Self::Deserialize(_))
+    }
+}
+
+impl std::fmt::Display for MongooseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(_) => f.write_str("no document found"),
+            Self::InsertOne(_) => f.write_str("error inserting document"),
+            Self::BulkInsert(_) => f.write_str("error bulk inserting documents"),
+            Self::List(_) => f.write_str("error listing documents"),
+            Self::Update(_) => f.write_str("error updating document"),
+            Self::BulkUpdate(_) => f.write_str("error bulk updating documents"),
+            Self::Delete(_) => f.write_str("error deleting document"),
+            Self::BulkDelete(_) => f.write_str("error bulk deleting documents"),
+            Self::Count(_) => f.write_str("error counting documents"),
+            Self::Aggregate(_) => f.write_str("error aggregating documents"),
+            Self::CreateIndex(_) => f.write_str("error creating indexes"),
+            Self::SyncIndexes(_) => f.write_str("error syncing indexes"),
+            Self::Schema(_) => f.write_str("error applying schema"),
+            Self::Connection(_) => f.write_str("error connecting to database"),
+            Self::Tenant(message) => write!(f, "error resolving tenant: {message}"),
+            Self::Transaction(_) => f.write_str("error running transaction"),
+            Self::TransientTransaction(_) => f.write_str("transient transaction error"),
+            Self::Watch(_) => f.write_str("error watching collection"),
+            Self::Pagination(_) => f.write_str("error paginating documents"),
+            Self::Migration(_) => f.write_str("error running migrations"),
+            Self::Hook(message) => write!(f, "aborted by hook: {message}"),
+            Self::InvalidUpdate(message) => write!(f, "invalid update: {message}"),
+            Self::InvalidQuery(message) => write!(f, "invalid query: {message}"),
+            Self::Validation(errors) => write!(f, "validation failed: {errors}"),
+            Self::Deserialize(_) => f.write_str("error deserializing document"),
+        }
+    }
+}
+
+impl std::error::Error for MongooseError {
+    // the wrapped error itself, so it downcasts to e.g. `mongodb::error::Error`, or the
+    // `ErrorSource` when only its message is left after a round trip through serde
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        let source = self.error_source()?;
+        Some(
+            source
+                .inner()
+                .map_or(source as &(dyn std::error::Error + 'static), |error| {
+                    error as &(dyn std::error::Error + 'static)
+                }),
+        )
+    }
+}
+
+const DUPLICATE_KEY: i32 = 11000;
+const WRITE_CONFLICT: i32 = 112;
+// MaxTimeMSExpired, ExceededTimeLimit
+const TIMEOUT_CODES: [i32; 2] = [50, 262];
+// the server's retryable read/write codes, see the driver's retryability spec
+const RETRYABLE_CODES: [i32; 13] = [
+    11600, 11602, 10107, 13435, 13436, 189, 91, 7, 6, 89, 9001, 134, 262,
+];
+
+// the underlying error of a `MongooseError`, serialized as just its message
+#[derive(Debug, Clone)]
+pub struct ErrorSource {
+    message: String,
+    error: Option<Arc<dyn std::error::Error + Send + Sync>>,
+}
+
+impl ErrorSource {
+    pub fn new(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        Self {
+            message: error.to_string(),
+            error: Some(Arc::new(error)),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    // the wrapped error, `None` for errors built from a message
+    pub fn inner(&self) -> Option<&(dyn std::error::Error + Send + Sync + 'static)> {
+        self.error.as_deref()
+    }
+
+    // the driver error, when the failure came from mongodb
+    pub fn driver(&self) -> Option<&mongodb::error::Error> {
+        self.inner()?.downcast_ref()
+    }
+
+    fn is_deserialize(&self) -> bool {
+        let Some(error) = self.error.as_deref() else {
+            return false;
+        };
+        error.is::<bson::de::Error>()
+            || self
+                .driver()
+                .is_some_and(|error| matches!(*error.kind, ErrorKind::BsonDeserialization(_)))
+    }
+}
+
+impl From<String> for ErrorSource {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            error: None,
+        }
+    }
+}
+
+impl From<&str> for ErrorSource {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl std::fmt::Display for ErrorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ErrorSource {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error
+            .as_deref()
+            .map(|error| error as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl Serialize for ErrorSource {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.message)
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorSource {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    // name of the unique index that was violated
+    pub index: String,
+    // the duplicated values as reported by the server, e.g. `{ username: "jude" }`
+    pub key: String,
+}
+
+impl DuplicateKey {
+    // E11000 duplicate key error collection: db.users index: username_1 dup key: { username: "jude" }
+    pub(crate) fn parse(message: &str) -> Option<Self> {
+        let (_, rest) = message.split_once(" index: ")?;
+        let (index, key) = rest.split_once(" dup key: ").unwrap_or((rest, ""));
+        Some(Self {
+            index: index.trim().to_string(),
+            key: key.trim().to_string(),
+        })
     }
 }
@@ -0,0 +1,244 @@
+use crate::types::MongooseError;
+use bson::{doc, Bson, Document};
+use serde::Serialize;
+
+// operators whose paths are checked for conflicts, `$set` is merged separately
+// any other `$` key is passed through untouched for the server to judge
+const OPERATORS: [&str; 14] = [
+    "$inc",
+    "$push",
+    "$pull",
+    "$pullAll",
+    "$pop",
+    "$unset",
+    "$setOnInsert",
+    "$rename",
+    "$min",
+    "$max",
+    "$mul",
+    "$currentDate",
+    "$addToSet",
+    "$bit",
+];
+
+// `a.b` overlaps with `a` and `a.b.c`, but not with `a.bc`
+pub(crate) fn overlaps(a: &str, b: &str) -> bool {
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    longer
+        .strip_prefix(shorter)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+fn operand<'a>(operator: &str, value: &'a Bson) -> Result<&'a Document, MongooseError> {
+    value.as_document().ok_or_else(|| {
+        MongooseError::InvalidUpdate(format!("{operator} expects a document, got {value}"))
+    })
+}
+
+// whether a pipeline stage writes `updated_at` itself
+#[cfg(feature = "timestamps")]
+fn sets_timestamp(stage: &Document) -> bool {
+    stage.iter().any(|(name, spec)| {
+        matches!(name.as_str(), "$set" | "$addFields" | "$project")
+            && spec
+                .as_document()
+                .is_some_and(|spec| spec.keys().any(|path| overlaps(path, "updated_at")))
+    })
+}
+
+// appends a stage stamping `updated_at` after the caller's stages, unless the caller sets it
+pub(crate) fn pipeline(stages: Vec<Document>) -> Vec<Document> {
+    #[cfg(feature = "timestamps")]
+    if !stages.iter().any(sets_timestamp) {
+        return stages
+            .into_iter()
+            .chain(std::iter::once(
+                doc! { "$set": { "updated_at": bson::DateTime::now() } },
+            ))
+            .collect();
+    }
+    stages
+}
+
+// merges plain keys into `$set`, stamps `updated_at` and rejects paths touched twice
+pub(crate) fn normalize(updates: &Document) -> Result<Document, MongooseError> {
+    let mut set_updates = Document::new();
+    let mut document_updates = Document::new();
+    let mut paths: Vec<(String, &str)> = vec![];
+    for (key, value) in updates {
+        if key == "$set" {
+            for (path, value) in operand(key, value)? {
+                set_updates.insert(path, value.clone());
+                paths.push((path.clone(), "$set"));
+            }
+        } else if let Some(operator) = OPERATORS.iter().find(|operator| *operator == key) {
+            let fields = operand(operator, value)?;
+            for (path, value) in fields {
+                paths.push((path.clone(), operator));
+                // both sides of a rename are written to
+                if let (&"$rename", Some(target)) = (operator, value.as_str()) {
+                    paths.push((target.to_string(), operator));
+                }
+            }
+            document_updates.insert(key, fields.clone());
+        } else if key.starts_with('$') {
+            document_updates.insert(key, value.clone());
+        } else {
+            // all other document field updates contained in $set
+            set_updates.insert(key, value.clone());
+            paths.push((key.clone(), "$set"));
+        }
+    }
+    // update timestamp, unless the caller manages it
+    #[cfg(feature = "timestamps")]
+    if !paths.iter().any(|(path, _)| overlaps(path, "updated_at")) {
+        set_updates.insert("updated_at", bson::DateTime::now());
+    }
+    for (index, (path, operator)) in paths.iter().enumerate() {
+        if let Some((other, other_operator)) = paths[index + 1..]
+            .iter()
+            .find(|(other, _)| overlaps(path, other))
+        {
+            return Err(MongooseError::InvalidUpdate(format!(
+                "conflicting update paths {path:?} ({operator}) and {other:?} ({other_operator})"
+            )));
+        }
+    }
+    if !set_updates.is_empty() {
+        document_updates.insert("$set", set_updates);
+    }
+    // overall document now looks something like:
+    // { $set: { "updated_at": Date, ... }, "$inc": { ... }, "$push": { ... } }
+    Ok(document_updates)
+}
+
+// typed alternative to hand written update documents, values are serialized through serde
+//
+// User::update(
+//     doc! { "_id": id },
+//     Update::new().set("address.city", "Denver").inc("age", 1).build()?,
+// )
+#[derive(Debug, Clone, Default)]
+pub struct Update {
+    document: Document,
+    // first value that failed to serialize, reported by `build`
+    error: Option<String>,
+}
+
+impl Update {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn serialize(&mut self, path: &str, value: impl Serialize) -> Bson {
+        bson::to_bson(&value).unwrap_or_else(|err| {
+            self.error.get_or_insert_with(|| format!("{path}: {err}"));
+            Bson::Null
+        })
+    }
+
+    fn operation(mut self, operator: &str, path: impl AsRef<str>, value: Bson) -> Self {
+        let fields = self
+            .document
+            .entry(operator.to_string())
+            .or_insert_with(|| Bson::Document(Document::new()));
+        if let Bson::Document(fields) = fields {
+            fields.insert(path.as_ref(), value);
+        }
+        self
+    }
+
+    fn value(mut self, operator: &str, path: impl AsRef<str>, value: impl Serialize) -> Self {
+        let value = self.serialize(path.as_ref(), value);
+        self.operation(operator, path, value)
+    }
+
+    #[must_use]
+    pub fn set(self, path: impl AsRef<str>, value: impl Serialize) -> Self {
+        self.value("$set", path, value)
+    }
+
+    #[must_use]
+    pub fn set_on_insert(self, path: impl AsRef<str>, value: impl Serialize) -> Self {
+        self.value("$setOnInsert", path, value)
+    }
+
+    #[must_use]
+    pub fn inc(self, path: impl AsRef<str>, amount: impl Serialize) -> Self {
+        self.value("$inc", path, amount)
+    }
+
+    #[must_use]
+    pub fn mul(self, path: impl AsRef<str>, factor: impl Serialize) -> Self {
+        self.value("$mul", path, factor)
+    }
+
+    #[must_use]
+    pub fn min(self, path: impl AsRef<str>, value: impl Serialize) -> Self {
+        self.value("$min", path, value)
+    }
+
+    #[must_use]
+    pub fn max(self, path: impl AsRef<str>, value: impl Serialize) -> Self {
+        self.value("$max", path, value)
+    }
+
+    #[must_use]
+    pub fn push(self, path: impl AsRef<str>, value: impl Serialize) -> Self {
+        self.value("$push", path, value)
+    }
+
+    // `slice` keeps the first (positive) or last (negative) n elements
+    // `sort` is `1` / `-1` or a document of fields for arrays of documents
+    #[must_use]
+    pub fn push_each<T: Serialize>(
+        mut self,
+        path: impl AsRef<str>,
+        values: impl IntoIterator<Item = T>,
+        slice: Option<i32>,
+        sort: Option<Bson>,
+    ) -> Self {
+        let values = values
+            .into_iter()
+            .map(|value| self.serialize(path.as_ref(), value))
+            .collect::<Vec<_>>();
+        let mut modifiers = doc! { "$each": values };
+        if let Some(slice) = slice {
+            modifiers.insert("$slice", slice);
+        }
+        if let Some(sort) = sort {
+            modifiers.insert("$sort", sort);
+        }
+        self.operation("$push", path, Bson::Document(modifiers))
+    }
+
+    // `value` may also be a condition, e.g. `doc! { "$gte": 6 }`
+    #[must_use]
+    pub fn pull(self, path: impl AsRef<str>, value: impl Serialize) -> Self {
+        self.value("$pull", path, value)
+    }
+
+    #[must_use]
+    pub fn add_to_set(self, path: impl AsRef<str>, value: impl Serialize) -> Self {
+        self.value("$addToSet", path, value)
+    }
+
+    #[must_use]
+    pub fn unset(self, path: impl AsRef<str>) -> Self {
+        self.operation("$unset", path, Bson::String(String::new()))
+    }
+
+    #[must_use]
+    pub fn rename(self, path: impl AsRef<str>, new_path: impl AsRef<str>) -> Self {
+        let new_path = Bson::String(new_path.as_ref().to_string());
+        self.operation("$rename", path, new_path)
+    }
+
+    // the update document for `Model::update` / `Model::bulk_update`
+    pub fn build(self) -> Result<Document, MongooseError> {
+        match self.error {
+            Some(error) => Err(MongooseError::InvalidUpdate(error)),
+            None => Ok(self.document),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    sync::{PoisonError, RwLock},
+};
+
+#[cfg(feature = "derive")]
+pub use mongoose_derive::Validate;
+
+// implemented by nested structs so their errors can be reported under the parent path
+// see `#[derive(Validate)]` and `#[validate(nested)]`
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    // dotted path of the offending field, e.g. `address.zip`
+    pub path: String,
+    // machine readable name of the failed check, e.g. `length`
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(
+        &mut self,
+        path: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.errors.push(FieldError {
+            path: path.into(),
+            code: code.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    // errors for a given path
+    pub fn field(&self, path: &str) -> Vec<&FieldError> {
+        self.errors
+            .iter()
+            .filter(|error| error.path == path)
+            .collect()
+    }
+
+    // merges the errors of a nested value, prefixing their paths with `path`
+    pub fn nest(&mut self, path: &str, result: Result<(), Self>) {
+        if let Err(nested) = result {
+            self.errors
+                .extend(nested.errors.into_iter().map(|error| FieldError {
+                    path: format!("{path}.{}", error.path),
+                    ..error
+                }));
+        }
+    }
+
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self
+            .errors
+            .iter()
+            .map(|error| format!("{}: {}", error.path, error.message))
+            .collect::<Vec<_>>();
+        write!(f, "{}", errors.join(", "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+// anything with a length, strings count characters rather than bytes
+pub trait Length {
+    fn length(&self) -> usize;
+}
+
+impl Length for str {
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl Length for String {
+    fn length(&self) -> usize {
+        self.as_str().length()
+    }
+}
+
+impl<T> Length for [T] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> Length for Vec<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+// built-in validators, each records a `FieldError` under `path` when the check fails
+pub fn length(
+    errors: &mut ValidationErrors,
+    path: &str,
+    value: &(impl Length + ?Sized),
+    min: Option<usize>,
+    max: Option<usize>,
+) {
+    let length = value.length();
+    let message = match (min, max) {
+        (Some(min), Some(max)) if length < min || length > max => {
+            format!("length must be between {min} and {max}")
+        }
+        (Some(min), _) if length < min => format!("length must be at least {min}"),
+        (_, Some(max)) if length > max => format!("length must be at most {max}"),
+        _ => return,
+    };
+    errors.add(path, "length", message);
+}
+
+pub fn range<T: PartialOrd + Display>(
+    errors: &mut ValidationErrors,
+    path: &str,
+    value: &T,
+    min: Option<T>,
+    max: Option<T>,
+) {
+    let message = match (min, max) {
+        (Some(min), Some(max)) if *value < min || *value > max => {
+            format!("must be between {min} and {max}")
+        }
+        (Some(min), _) if *value < min => format!("must be at least {min}"),
+        (_, Some(max)) if *value > max => format!("must be at most {max}"),
+        _ => return,
+    };
+    errors.add(path, "range", message);
+}
+
+// a pragmatic check, not a full RFC 5322 parser
+pub fn email(errors: &mut ValidationErrors, path: &str, value: &str) {
+    let valid = value.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && !domain.contains('@')
+            && domain.contains('.')
+            && !domain.starts_with('.')
+            && !domain.ends_with('.')
+            && !value.chars().any(char::is_whitespace)
+    });
+    if !valid {
+        errors.add(path, "email", "must be a valid email address");
+    }
+}
+
+lazy_static! {
+    static ref PATTERNS: RwLock<HashMap<String, Regex>> = RwLock::new(HashMap::new());
+}
+
+// patterns are compiled once and cached
+pub fn regex(errors: &mut ValidationErrors, path: &str, value: &str, pattern: &str) {
+    let cached = PATTERNS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(pattern)
+        .map(|regex| regex.is_match(value));
+    let matched = match cached {
+        Some(matched) => matched,
+        None => match Regex::new(pattern) {
+            Ok(regex) => {
+                let matched = regex.is_match(value);
+                PATTERNS
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(pattern.to_string(), regex);
+                matched
+            }
+            Err(err) => {
+                tracing::error!("invalid validation pattern {:?}: {:?}", pattern, err);
+                return errors.add(path, "regex", format!("invalid pattern {pattern:?}"));
+            }
+        },
+    };
+    if !matched {
+        errors.add(path, "regex", format!("must match {pattern:?}"));
+    }
+}
+
+pub fn one_of<T: PartialEq<A> + ?Sized, A: Debug>(
+    errors: &mut ValidationErrors,
+    path: &str,
+    value: &T,
+    allowed: &[A],
+) {
+    if !allowed.iter().any(|option| value == option) {
+        errors.add(path, "one_of", format!("must be one of {allowed:?}"));
+    }
+}